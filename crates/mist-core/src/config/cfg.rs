@@ -1,4 +1,5 @@
-use super::{Colors, Font, KeybindsRaw, Panel};
+use super::{Colors, Font, KeybindsRaw, Panel, ResetPolicy};
+use crate::error::ConfigError;
 use directories::BaseDirs;
 use ron::{
     de::from_reader,
@@ -28,20 +29,25 @@ pub struct Config {
     s_font: Font,
     ms_ratio: f32,
     binds: KeybindsRaw,
+    reset_policy: ResetPolicy,
+    notes_lines: u32,
+    autosave_interval: u64,
+    practice_golds: bool,
 }
 
 impl Config {
     /// Attempts to open and parse mist's default config.
     ///
     /// If a Config cannot be parsed, returns the default.
-    /// Only will return `Err` if it cannot read the config file.
-    pub fn open() -> Result<Self, String> {
+    /// Only will return `Err` if it cannot find or read the config file.
+    pub fn open() -> Result<Self, ConfigError> {
+        let path = config_path()?;
         let file = OpenOptions::new()
             .read(true)
             .write(true)
             .create(true)
-            .open(config_path()?)
-            .map_err(|e| e.to_string())?;
+            .open(&path)
+            .map_err(|source| ConfigError::Io { path, source })?;
         let cfg = from_reader(&file);
         Ok(cfg.unwrap_or_default())
     }
@@ -79,21 +85,21 @@ impl Config {
     ///
     /// # Errors
     ///
-    /// * If the serialization fails.
-    /// * If the file cannot be written to or opened.
-    pub fn save(&self) -> Result<(), String> {
-        let mut file = OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .open(config_path()?)
-            .map_err(|e| e.to_string())?;
+    /// * [`ConfigError::Serialize`] if the serialization fails.
+    /// * [`ConfigError::Io`] if the file cannot be written to or opened.
+    pub fn save(&self) -> Result<(), ConfigError> {
+        let path = config_path()?;
         let string = to_string_pretty(
             self,
             PrettyConfig::new().extensions(Extensions::IMPLICIT_SOME),
         )
-        .map_err(|e| e.to_string())?;
-        file.write(string.as_bytes()).map_err(|e| e.to_string())?;
-        Ok(())
+        .map_err(ConfigError::Serialize)?;
+        OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(string.as_bytes()))
+            .map_err(|source| ConfigError::Io { path, source })
     }
     /// Get the keybinds in string form as names of keys.
     pub fn binds(&self) -> &KeybindsRaw {
@@ -124,6 +130,24 @@ impl Config {
     pub fn set_win_size(&mut self, new: (u32, u32)) {
         self.win_size = new;
     }
+    /// Get what should happen to golds from attempts that are reset.
+    pub fn reset_policy(&self) -> ResetPolicy {
+        self.reset_policy
+    }
+    /// Get the number of lines of split notes to show below the panels.
+    /// 0 means the notes area is hidden.
+    pub fn notes_lines(&self) -> u32 {
+        self.notes_lines
+    }
+    /// Get the number of seconds between saves of the run in progress for crash recovery.
+    /// 0 means the run is never saved for recovery.
+    pub fn autosave_interval(&self) -> u64 {
+        self.autosave_interval
+    }
+    /// Get whether golds set while practicing are saved to the run.
+    pub fn practice_golds(&self) -> bool {
+        self.practice_golds
+    }
 }
 
 impl Default for Config {
@@ -143,16 +167,38 @@ impl Default for Config {
             s_font: Font::splits_default(),
             ms_ratio: 1.0,
             binds: KeybindsRaw::default(),
+            reset_policy: ResetPolicy::default(),
+            notes_lines: 0,
+            autosave_interval: 30,
+            practice_golds: false,
         }
     }
 }
 
-fn config_path() -> Result<PathBuf, String> {
-    let dirs = BaseDirs::new().ok_or("Could not find your config directory!")?;
+/// Get the path that the run in progress is saved to so that it can be recovered after a crash.
+pub fn recovery_path() -> Result<PathBuf, ConfigError> {
+    let dirs = BaseDirs::new().ok_or(ConfigError::NoHomeDir)?;
+    let mut path = dirs.data_dir().to_path_buf();
+    path.push("mist");
+    if !path.exists() {
+        std::fs::create_dir_all(&path).map_err(|source| ConfigError::Io {
+            path: path.clone(),
+            source,
+        })?;
+    }
+    path.push("recovery.dump");
+    Ok(path)
+}
+
+fn config_path() -> Result<PathBuf, ConfigError> {
+    let dirs = BaseDirs::new().ok_or(ConfigError::NoHomeDir)?;
     let mut cfg_path = dirs.config_dir().to_path_buf();
     cfg_path.push("mist");
     if !cfg_path.exists() {
-        std::fs::create_dir_all(&cfg_path).map_err(|e| e.to_string())?;
+        std::fs::create_dir_all(&cfg_path).map_err(|source| ConfigError::Io {
+            path: cfg_path.clone(),
+            source,
+        })?;
     }
     cfg_path.push("mist.cfg");
     if !cfg_path.exists() {
@@ -160,7 +206,10 @@ fn config_path() -> Result<PathBuf, String> {
             .create(true)
             .write(true)
             .open(&cfg_path)
-            .map_err(|e| e.to_string())?;
+            .map_err(|source| ConfigError::Io {
+                path: cfg_path.clone(),
+                source,
+            })?;
     }
     Ok(cfg_path)
 }
@@ -16,6 +16,8 @@ pub struct Colors {
     pub background: (u8, u8, u8, u8),
     /// The color for split name text.
     pub text: (u8, u8, u8, u8),
+    /// The color of the timer once a countdown has run out.
+    pub time_up: (u8, u8, u8, u8),
 }
 
 impl Default for Colors {
@@ -30,6 +32,7 @@ impl Default for Colors {
             highlight: (0, 0, 255, 255),
             background: (0, 0, 0, 0),
             text: (255, 255, 255, 255),
+            time_up: (200, 0, 255, 255),
         }
     }
 }
@@ -1,9 +1,9 @@
+use crate::error::FontError;
 use font_kit::{
     family_name::FamilyName, handle::Handle, properties::Properties, source::SystemSource,
 };
 use serde::{Deserialize, Serialize};
-use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -75,10 +75,21 @@ impl From<&Style> for font_kit::properties::Style {
 
 impl Font {
     /// Get the path to the font file, and the index of the font, as determined by `font_kit`.
-    pub fn get_bytes(&self) -> Result<(Arc<Vec<u8>>, u32), String> {
+    ///
+    /// # Errors
+    ///
+    /// * [`FontError::NotFound`] if no installed font matches.
+    /// * [`FontError::Io`] if the font file cannot be read.
+    pub fn get_bytes(&self) -> Result<(Arc<Vec<u8>>, u32), FontError> {
         self.ty.get_bytes()
     }
-    pub fn get_path(&self) -> Result<(PathBuf, u32), String> {
+    /// Get the path to the font file, and the index of the font.
+    ///
+    /// # Errors
+    ///
+    /// * [`FontError::NotFound`] if no installed font matches.
+    /// * [`FontError::NoPath`] if the font is only available in memory.
+    pub fn get_path(&self) -> Result<(PathBuf, u32), FontError> {
         self.ty.get_path()
     }
     /// Get the size of the font.
@@ -102,14 +113,9 @@ impl Font {
 }
 
 impl FontType {
-    fn get_bytes(&self) -> Result<(Arc<Vec<u8>>, u32), String> {
+    fn get_bytes(&self) -> Result<(Arc<Vec<u8>>, u32), FontError> {
         match self {
-            Self::File { path } => {
-                let mut buf = vec![];
-                let mut f = std::fs::File::open(path).unwrap();
-                f.read_to_end(&mut buf).unwrap();
-                Ok((Arc::new(buf), 0))
-            }
+            Self::File { path } => Ok((Arc::new(read_font(path)?), 0)),
             Self::System {
                 name,
                 style,
@@ -130,20 +136,20 @@ impl FontType {
                 };
                 let handle = SystemSource::new()
                     .select_best_match(&[family_name], &props)
-                    .map_err(|_| format!("Could not locate font {name} {style:?}"))?;
+                    .map_err(|source| FontError::NotFound {
+                        name: name.to_owned(),
+                        source,
+                    })?;
                 match handle {
                     Handle::Path { path, font_index } => {
-                        let mut buf = vec![];
-                        let mut f = std::fs::File::open(path).unwrap();
-                        f.read_to_end(&mut buf).unwrap();
-                        Ok((Arc::new(buf), font_index))
+                        Ok((Arc::new(read_font(&path)?), font_index))
                     }
                     Handle::Memory { bytes, font_index } => Ok((bytes, font_index)),
                 }
             }
         }
     }
-    fn get_path(&self) -> Result<(PathBuf, u32), String> {
+    fn get_path(&self) -> Result<(PathBuf, u32), FontError> {
         match self {
             Self::File { path } => Ok((path.into(), 0)),
             Self::System {
@@ -166,10 +172,13 @@ impl FontType {
                 };
                 let handle = SystemSource::new()
                     .select_best_match(&[family_name], &props)
-                    .map_err(|_| format!("Could not locate font {name} {style:?}"))?;
+                    .map_err(|source| FontError::NotFound {
+                        name: name.to_owned(),
+                        source,
+                    })?;
                 match handle {
                     Handle::Path { path, font_index } => Ok((path, font_index)),
-                    _ => Err(String::from("Font not accessible as a path")),
+                    _ => Err(FontError::NoPath),
                 }
             }
         }
@@ -189,3 +198,10 @@ impl FontType {
         }
     }
 }
+
+fn read_font(path: &Path) -> Result<Vec<u8>, FontError> {
+    std::fs::read(path).map_err(|source| FontError::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
@@ -14,6 +14,15 @@ pub struct KeybindsRaw {
     pub load_config: String,
     pub dump_state: String,
     pub load_state: String,
+    pub pause_game_time: String,
+    pub switch_method: String,
+    pub undo_reset: String,
+    pub switch_category: String,
+    pub load_ghost: String,
+    pub practice: String,
+    pub load_marathon: String,
+    #[cfg(feature = "lss")]
+    pub save_lss: String,
 }
 
 impl Default for KeybindsRaw {
@@ -30,6 +39,15 @@ impl Default for KeybindsRaw {
             load_config: "F2".to_owned(),
             dump_state: "F3".to_owned(),
             load_state: "F4".to_owned(),
+            pause_game_time: "L".to_owned(),
+            switch_method: "T".to_owned(),
+            undo_reset: "U".to_owned(),
+            switch_category: "C".to_owned(),
+            load_ghost: "G".to_owned(),
+            practice: "P".to_owned(),
+            load_marathon: "M".to_owned(),
+            #[cfg(feature = "lss")]
+            save_lss: "F5".to_owned(),
         }
     }
 }
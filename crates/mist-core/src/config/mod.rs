@@ -4,4 +4,12 @@ mod colors;
 mod font;
 mod keybinds;
 mod panels;
-pub use {cfg::Config, colors::Colors, font::Font, keybinds::KeybindsRaw, panels::Panel};
+mod reset_policy;
+pub use {
+    cfg::{recovery_path, Config},
+    colors::Colors,
+    font::Font,
+    keybinds::KeybindsRaw,
+    panels::Panel,
+    reset_policy::ResetPolicy,
+};
@@ -11,4 +11,18 @@ pub enum Panel {
     CurrentSplitDiff { golds: bool },
     /// Prediction of final run time, based on either pb times or golds.
     Pace { golds: bool },
+    /// Time that could be saved on the current split, the comparison minus the gold
+    PossibleTimeSave,
+    /// Time that could be saved on the rest of the run
+    TotalTimeSave,
+    /// Fastest time the run could still end with, the current time plus the remaining golds
+    BestPossibleTime,
+    /// Difference between the last split's time and its comparison
+    PreviousSegment,
+    /// Median and standard deviation of the current split's past times
+    SegmentStats,
+    /// How often attempts have been reset on the current split
+    ResetRate,
+    /// Real time since the first run of a marathon was started, including the time between runs
+    MarathonTotal,
 }
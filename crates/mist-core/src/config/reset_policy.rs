@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// What to do with new golds from an attempt that was reset before it finished.
+pub enum ResetPolicy {
+    /// Save the golds without asking.
+    #[default]
+    Keep,
+    /// Ask whether to save the golds on each reset that has any.
+    Ask,
+    /// Throw the golds away.
+    Discard,
+}
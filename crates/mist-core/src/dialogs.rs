@@ -4,6 +4,9 @@
 //! in a terminal if none of the dialog APIs it's expecting are available.
 #[cfg(feature = "config")]
 use crate::config::Config;
+#[cfg(feature = "config")]
+use crate::error::{ConfigError, ParseError};
+use std::fmt::Display;
 use std::fs::File;
 use tinyfiledialogs::{
     message_box_ok, message_box_yes_no, open_file_dialog, save_file_dialog_with_filter,
@@ -27,6 +30,39 @@ pub fn save_check() -> bool {
     )
 }
 
+/// Check if the user wants to keep the golds from an attempt that was reset.
+///
+/// If they click yes, return `true`. No returns `false`.
+pub fn keep_golds_check() -> bool {
+    boolean_check(
+        "Keep golds?",
+        "The attempt you reset had new best segments, do you want to keep them?",
+    )
+}
+
+/// Check if the user wants to resume a run that was interrupted.
+///
+/// If they click yes, return `true`. No returns `false`.
+pub fn resume_check() -> bool {
+    boolean_check(
+        "Resume run?",
+        "mist was closed in the middle of a run, do you want to pick up where you left off?",
+    )
+}
+
+/// Tell the user why the run that was interrupted can't be resumed.
+pub fn resume_failed(reason: &str) {
+    let msg = format!(
+        "mist was closed in the middle of a run, but it can't be resumed: {}",
+        reason
+    );
+    message_box_ok(
+        "Can't resume run",
+        &msg.replace(['\'', '"'], ""),
+        MessageBoxIcon::Warning,
+    );
+}
+
 /// Open a file select dialog box.
 ///
 /// Box title will be `title`. `filter` should be formatted like `*.msf` to filter for msf file extensions etc.
@@ -42,12 +78,29 @@ pub fn get_save_as() -> Option<String> {
     save_file_dialog_with_filter("Save as:", "", &["*.msf"], "mist split files")
 }
 
-/// Ask the user if they want to try another file.
-pub fn try_again() -> bool {
-    boolean_check(
-        "File parse failed",
-        "File parse failed. Do you want to try another?",
+/// Open a save as dialog box for a LiveSplit split file, adding the `.lss` extension if it is missing.
+///
+/// Returns `None` if the user closes the dialog box or presses Cancel.
+#[cfg(feature = "lss")]
+pub fn get_lss_save_as() -> Option<String> {
+    save_file_dialog_with_filter(
+        "Save as LiveSplit file:",
+        "",
+        &["*.lss"],
+        "LiveSplit split files",
     )
+    .map(|mut p| {
+        if !p.ends_with(".lss") {
+            p.push_str(".lss");
+        }
+        p
+    })
+}
+
+/// Ask the user if they want to try another file, after showing them why the last one failed.
+pub fn try_again(err: &dyn Display) -> bool {
+    let msg = format!("File parse failed: {}\n\nDo you want to try another?", err);
+    boolean_check("File parse failed", &msg.replace(['\'', '"'], ""))
 }
 
 /// Get the path of an msf file to use.
@@ -57,6 +110,25 @@ pub fn get_run_path() -> Option<String> {
     get_file("Open split file", "*.msf")
 }
 
+/// Get the path of a split file to race against as a ghost.
+///
+/// Also offers lss files when built with the `lss` feature.
+/// Returns [`None`] if the user cancels the dialog box
+pub fn get_ghost_path() -> Option<String> {
+    #[cfg(feature = "lss")]
+    let filters: &[&str] = &["*.msf", "*.lss"];
+    #[cfg(not(feature = "lss"))]
+    let filters: &[&str] = &["*.msf"];
+    open_file_dialog("Open ghost split file", "", Some((filters, "")))
+}
+
+/// Get the path of a marathon playlist to run.
+///
+/// Returns [`None`] if the user cancels the dialog box
+pub fn get_marathon_path() -> Option<String> {
+    get_file("Open marathon playlist", "*.txt")
+}
+
 /// Get the path of a state dump file to use.
 ///
 /// Returns [`None`] if the user cancels the dialog box
@@ -75,26 +147,29 @@ pub fn get_dump_save() -> Option<String> {
 ///
 /// # Errors
 ///
-/// * If the file cannot be read or there is another fs error.
+/// * [`ConfigError::Io`] if the file cannot be read or there is another fs error.
 ///
 /// # Nones
 ///
 /// * If the user does not select a file.
 /// * If the file selected cannot be parsed into a [`Config`].
 #[cfg(feature = "config")]
-pub fn open_config() -> Result<Option<Config>, String> {
+pub fn open_config() -> Result<Option<Config>, ConfigError> {
     loop {
         match get_file("Open a config file", "*.cfg") {
             Some(ref p) => {
-                let f = File::open(p).map_err(|e| e.to_string())?;
-                let config: Result<Config, String> =
-                    ron::de::from_reader(f).map_err(|e| e.to_string());
+                let f = File::open(p).map_err(|source| ConfigError::Io {
+                    path: p.into(),
+                    source,
+                })?;
+                let config: Result<Config, ParseError> =
+                    ron::de::from_reader(f).map_err(ParseError::syntax);
                 match config {
                     Ok(c) => {
                         return Ok(Some(c));
                     }
-                    Err(_) => {
-                        if !try_again() {
+                    Err(e) => {
+                        if !try_again(&e) {
                             return Ok(None);
                         }
                     }
@@ -0,0 +1,208 @@
+//! Errors that can happen in mist-core.
+//!
+//! Each part of the crate returns its own error type so that callers can tell what went wrong, e.g.
+//! a missing file from a malformed one. All of them can be converted into an [`Error`] for callers
+//! that only want to show a message.
+//!
+//! Their messages already include whatever error caused them, so none of them return a
+//! [`source`](std::error::Error::source), which would have error reports print it twice.
+#[cfg(feature = "config")]
+use std::path::PathBuf;
+use std::{error::Error as StdError, fmt, io};
+
+/// A position in a file. Lines and columns both start at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// An error from reading or writing a split file or a [`StateDump`](crate::timer::dump::StateDump).
+#[derive(Debug)]
+pub enum ParseError {
+    /// The file could not be opened, read or written.
+    Io(io::Error),
+    /// The file was empty.
+    Empty,
+    /// The file is from a newer version of mist than this one.
+    UnsupportedVersion(u32),
+    /// The contents of the file are malformed.
+    Syntax {
+        /// Where in the file the problem is.
+        location: Location,
+        source: ron::Error,
+    },
+    /// The data could not be serialized.
+    Serialize(ron::Error),
+    /// The LiveSplit file is not valid XML.
+    #[cfg(feature = "lss")]
+    Xml {
+        /// Offset in bytes from the start of the file where the problem is.
+        position: usize,
+        source: quick_xml::Error,
+    },
+    /// A time, date or number in the LiveSplit file could not be read.
+    #[cfg(feature = "lss")]
+    InvalidValue {
+        /// Offset in bytes from the start of the file where the problem is.
+        position: usize,
+        value: String,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Could not access file: {}", e),
+            Self::Empty => write!(f, "File was empty"),
+            Self::UnsupportedVersion(v) => {
+                write!(f, "File version {} is newer than this version of mist", v)
+            }
+            Self::Syntax { location, source } => {
+                write!(f, "Malformed file at {}: {}", location, source.code)
+            }
+            Self::Serialize(e) => write!(f, "Could not serialize: {}", e),
+            #[cfg(feature = "lss")]
+            Self::Xml { position, source } => {
+                write!(f, "Malformed xml at byte {}: {}", position, source)
+            }
+            #[cfg(feature = "lss")]
+            Self::InvalidValue { position, value } => {
+                write!(f, "Invalid value {:?} at byte {}", value, position)
+            }
+        }
+    }
+}
+
+impl StdError for ParseError {}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl ParseError {
+    // a ron error from reading a file, with where in the file it happened
+    pub(crate) fn syntax(source: ron::Error) -> Self {
+        Self::Syntax {
+            location: Location {
+                line: source.position.line,
+                column: source.position.col,
+            },
+            source,
+        }
+    }
+}
+
+/// An error from finding, reading or writing mist's files other than split files.
+#[cfg(feature = "config")]
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The user's home directory, which mist keeps its files under, could not be found.
+    NoHomeDir,
+    /// A file or directory could not be accessed.
+    Io { path: PathBuf, source: io::Error },
+    /// The config could not be serialized.
+    Serialize(ron::Error),
+}
+
+#[cfg(feature = "config")]
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoHomeDir => write!(f, "Could not find your home directory"),
+            Self::Io { path, source } => {
+                write!(f, "Could not access {}: {}", path.display(), source)
+            }
+            Self::Serialize(e) => write!(f, "Could not serialize config: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+impl StdError for ConfigError {}
+
+/// An error from loading a [`Font`](crate::config::Font).
+#[cfg(feature = "config")]
+#[derive(Debug)]
+pub enum FontError {
+    /// No installed font matched the one in the config.
+    NotFound {
+        /// Family name of the font that was looked for.
+        name: String,
+        source: font_kit::error::SelectionError,
+    },
+    /// The font file could not be read.
+    Io { path: PathBuf, source: io::Error },
+    /// The font was found, but is only available in memory rather than as a file.
+    NoPath,
+}
+
+#[cfg(feature = "config")]
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound { name, source } => {
+                write!(f, "Could not locate font {}: {}", name, source)
+            }
+            Self::Io { path, source } => {
+                write!(f, "Could not read font {}: {}", path.display(), source)
+            }
+            Self::NoPath => write!(f, "Font not accessible as a path"),
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+impl StdError for FontError {}
+
+/// Any error from mist-core.
+#[derive(Debug)]
+pub enum Error {
+    Parse(ParseError),
+    #[cfg(feature = "config")]
+    Config(ConfigError),
+    #[cfg(feature = "config")]
+    Font(FontError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(e) => e.fmt(f),
+            #[cfg(feature = "config")]
+            Self::Config(e) => e.fmt(f),
+            #[cfg(feature = "config")]
+            Self::Font(e) => e.fmt(f),
+        }
+    }
+}
+
+impl StdError for Error {}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+#[cfg(feature = "config")]
+impl From<ConfigError> for Error {
+    fn from(e: ConfigError) -> Self {
+        Self::Config(e)
+    }
+}
+
+#[cfg(feature = "config")]
+impl From<FontError> for Error {
+    fn from(e: FontError) -> Self {
+        Self::Font(e)
+    }
+}
@@ -2,5 +2,6 @@
 pub mod config;
 #[cfg(feature = "dialogs")]
 pub mod dialogs;
+pub mod error;
 pub mod parse;
 pub mod timer;
@@ -1,132 +1,1024 @@
-use crate::timer::Run;
-use quick_xml::{events::Event, Reader};
-use std::{fs::File, io::BufReader};
+use crate::error::ParseError;
+use crate::timer::{Attempt, CustomComparison, Metadata, Run, TimeType, TimingMethod};
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+};
 
-fn str_to_ms(tm: &str) -> u128 {
-    if tm == "00:00:00" || tm.is_empty() {
+/// Name LiveSplit gives the comparison holding the personal best.
+const PERSONAL_BEST: &str = "Personal Best";
+
+// parse a LiveSplit time span, `[days.]hours:minutes:seconds[.fraction]`, into nanoseconds
+fn str_to_ns(tm: &str) -> Option<u128> {
+    fn number(s: &str) -> Option<u128> {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        s.parse().ok()
+    }
+    let mut parts = tm.split(':');
+    let (hours, min, sec) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    let (days, hours) = match hours.split_once('.') {
+        Some((d, h)) => (number(d)?, number(h)?),
+        None => (0, number(hours)?),
+    };
+    let (sec, frac) = sec.split_once('.').unwrap_or((sec, ""));
+    let (min, sec) = (number(min)?, number(sec)?);
+    if min >= 60 || sec >= 60 {
+        return None;
+    }
+    // anything past nanoseconds is dropped, and shorter fractions are padded out
+    let nanos = if frac.is_empty() {
         0
     } else {
-        let hr = &tm[0..2].parse::<u128>().unwrap();
-        let min = &tm[3..5].parse::<u128>().unwrap();
-        let sec = &tm[6..8].parse::<u128>().unwrap();
-        let ms = &tm[9..12].parse::<u128>().unwrap();
-        ms + (sec * 1000) + (min * 60000) + (hr * 3600000)
+        number(frac)?;
+        format!("{:0<9.9}", frac).parse::<u128>().ok()?
+    };
+    Some((((days * 24 + hours) * 60 + min) * 60 + sec) * 1_000_000_000 + nanos)
+}
+
+// convert a LiveSplit date, `month/day/year hours:minutes:seconds` in UTC, into milliseconds since
+// the unix epoch
+fn date_to_ms(date: &str) -> Option<u128> {
+    let (day, time) = date.split_once(' ')?;
+    let mut parts = day.split('/').map(|p| p.parse::<i64>().ok());
+    let (month, day, year) = (parts.next()??, parts.next()??, parts.next()??);
+    let mut parts = time.split(':').map(|p| p.parse::<i64>().ok());
+    let (hour, min, sec) = (parts.next()??, parts.next()??, parts.next()??);
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || !(0..24).contains(&hour)
+        || !(0..60).contains(&min)
+        || !(0..60).contains(&sec)
+    {
+        return None;
+    }
+    // days since the epoch in the proleptic gregorian calendar, with years starting in march
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    let secs = ((days * 24 + hour) * 60 + min) * 60 + sec;
+    if secs < 0 {
+        None
+    } else {
+        Some(secs as u128 * 1000)
     }
 }
 
-/// Constructs a [`Run`] from a LiveSplit split file.
+// format nanoseconds as a LiveSplit time span, which only goes down to 100 nanoseconds
+fn ns_to_str(ns: u128) -> String {
+    let ticks = ns / 100;
+    let secs = ticks / 10_000_000;
+    let (days, hours, min, sec) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    let time = format!(
+        "{:02}:{:02}:{:02}.{:07}",
+        hours,
+        min,
+        sec,
+        ticks % 10_000_000
+    );
+    if days > 0 {
+        format!("{}.{}", days, time)
+    } else {
+        time
+    }
+}
+
+// the inverse of `date_to_ms`
+fn ms_to_date(ms: u128) -> String {
+    let secs = (ms / 1000) as i64;
+    let (days, secs) = (secs / 86400, secs % 86400);
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = era * 400 + year_of_era + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:02}/{:02}/{:04} {:02}:{:02}:{:02}",
+        month,
+        day,
+        year,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+// LiveSplit stores the cumulative time at each split, while mist stores the time of each segment
+fn to_segments(cumulative: &[Option<u128>]) -> Vec<TimeType> {
+    let mut last = 0;
+    cumulative
+        .iter()
+        .map(|t| match *t {
+            Some(t) => {
+                let segment = t.saturating_sub(last);
+                last = t;
+                TimeType::Time(segment)
+            }
+            None => TimeType::None,
+        })
+        .collect()
+}
+
+// the inverse of `to_segments`, where the time of skipped segments is counted in the next split
+fn to_cumulative(segments: &[TimeType], len: usize) -> Vec<Option<u128>> {
+    let mut sum = 0;
+    (0..len)
+        .map(|i| {
+            let t = segments.get(i).copied().unwrap_or(TimeType::None);
+            sum += t.raw();
+            t.to_option().map(|_| sum)
+        })
+        .collect()
+}
+
+/// Reads and writes [`Run`]s as LiveSplit split files.
 ///
-/// Attempts to retrieve the relevant information from LiveSplit's XML-based split file
-/// in order to construct a mist [`Run`]. Any info that cannot be retrieved is zeroed.
+/// Reads the split names and the personal best, golds, segment history and custom comparisons in
+/// both real time and game time, along with the attempt history, the offset and the speedrun.com
+/// metadata.
 pub struct LssParser {
     filename: String,
 }
 
 impl LssParser {
-    /// Create a new [`LssParser`] from a [`BufReader`].
+    /// Create a new [`LssParser`] for the file at `filename`.
     pub fn new(filename: String) -> Self {
         Self { filename }
     }
 
-    /// Retrieve the information from the reader to create a [`Run`].
+    /// Read the file to create a [`Run`].
     ///
-    /// Returns a [`Run`] with all of the fields that were found filled in. This can return an empty [`Run`] if the LiveSplit file
-    /// was malformed or missing information.
-    pub fn parse(&mut self) -> Result<Run, String> {
-        let mut run = Run::empty();
+    /// Anything missing from the file is left empty in the [`Run`].
+    ///
+    /// # Errors
+    ///
+    /// * [`ParseError::Io`] if the file cannot be read from.
+    /// * [`ParseError::Xml`] if the file is not valid XML.
+    /// * [`ParseError::InvalidValue`] if a time, date or number in the file is malformed.
+    pub fn parse(&mut self) -> Result<Run, ParseError> {
+        let f = File::open(&self.filename)?;
+        parse_reader(BufReader::new(f))
+    }
 
-        let f = File::open(&self.filename).map_err(|e| e.to_string())?;
-        let mut reader = BufReader::new(f);
-        let mut reader = Reader::from_reader(&mut reader);
-        reader.check_end_names(false);
-
-        let mut buffer = vec![];
-        let mut buffer2 = vec![];
-        let mut time_str = String::new();
-        let mut splits = vec![];
-        let mut pb_times = vec![];
-        let mut gold_times = vec![];
-        let mut sum_times = vec![];
-        let mut segment_sum: (u128, u128) = (0, 0);
-        let mut pb = 0;
-
-        loop {
-            match reader.read_event(&mut buffer) {
-                Ok(Event::Start(ref e)) => match e.name() {
-                    b"GameName" => {
-                        run.set_game_title(
-                            reader
-                                .read_text(b"GameName", &mut buffer2)
-                                .unwrap_or(String::new()),
-                        );
-                    }
-                    b"CategoryName" => {
-                        run.set_category(
-                            reader
-                                .read_text(b"CategoryName", &mut buffer2)
-                                .unwrap_or(String::new()),
-                        );
-                    }
-                    b"Offset" => {
-                        let mut off_str = reader
-                            .read_text(b"Offset", &mut buffer2)
-                            .unwrap_or(String::new());
-                        off_str.remove(0);
-                        let t = str_to_ms(&off_str);
-                        run.set_offset(t.into());
-                    }
-                    b"Name" => {
-                        splits.push(
-                            reader
-                                .read_text(b"Name", &mut buffer2)
-                                .unwrap_or(String::new()),
-                        );
-                    }
-                    b"RealTime" => {
-                        time_str = reader
-                            .read_text(b"RealTime", &mut buffer2)
-                            .unwrap_or(String::new());
-                    }
-                    b"SegmentHistory" => {
-                        segment_sum = (0, 0);
-                    }
-                    _ => {}
-                },
-                Ok(Event::End(ref e)) => match e.name() {
-                    b"SplitTime" => {
-                        let t = str_to_ms(&time_str);
-                        if t != 0 {
-                            pb_times.push(t.into());
-                            pb += t;
-                        }
-                    }
-                    b"BestSegmentTime" => {
-                        let t = str_to_ms(&time_str);
-                        if t != 0 {
-                            gold_times.push(t.into())
-                        }
-                    }
-                    b"Time" => {
-                        let t = str_to_ms(&time_str);
-                        if t != 0 {
-                            segment_sum.0 += 1;
-                            segment_sum.1 += t;
-                        }
-                    }
-                    b"SegmentHistory" => {
-                        sum_times.push((segment_sum.0, segment_sum.1.into()));
-                    }
-                    _ => {}
+    /// Write `run` to the file as a LiveSplit split file.
+    ///
+    /// Times are rounded down to the 100 nanoseconds LiveSplit uses, and attempt dates to the
+    /// second. Things LiveSplit has no place for, like notes, groups and practice history, are
+    /// not written.
+    ///
+    /// # Errors
+    ///
+    /// * [`ParseError::Io`] if the file cannot be written to.
+    pub fn write(&mut self, run: &Run) -> Result<(), ParseError> {
+        let f = File::create(&self.filename)?;
+        write_run(run, BufWriter::new(f))?;
+        Ok(())
+    }
+}
+
+fn parse_reader<R: BufRead>(reader: R) -> Result<Run, ParseError> {
+    let mut reader = Reader::from_reader(reader);
+    reader.check_end_names(false);
+    reader.trim_text(true);
+    let mut lss = LssRun::default();
+    let mut buffer = vec![];
+    loop {
+        let event = reader.read_event(&mut buffer);
+        let position = reader.buffer_position();
+        match event {
+            Ok(Event::Start(ref e)) => lss.open(e, &reader, position)?,
+            Ok(Event::Empty(ref e)) => {
+                lss.open(e, &reader, position)?;
+                lss.close(position)?;
+            }
+            Ok(Event::End(_)) => lss.close(position)?,
+            Ok(Event::Text(ref e)) => {
+                lss.text = e
+                    .unescape_and_decode(&reader)
+                    .map_err(|e| xml_error(e, position))?;
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(xml_error(e, position)),
+            _ => {}
+        }
+        buffer.clear();
+    }
+    Ok(super::sanify_run(&lss.into_run()))
+}
+
+fn xml_error(e: quick_xml::Error, position: usize) -> ParseError {
+    match e {
+        quick_xml::Error::Io(e) => e.into(),
+        e => ParseError::Xml {
+            position,
+            source: e,
+        },
+    }
+}
+
+fn attribute<B: BufRead>(
+    e: &BytesStart,
+    key: &[u8],
+    reader: &Reader<B>,
+) -> Result<Option<String>, quick_xml::Error> {
+    for attr in e.attributes() {
+        let attr = attr?;
+        if attr.key == key {
+            return attr.unescape_and_decode_value(reader).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+// a real time and a game time
+type Times = (Option<u128>, Option<u128>);
+
+#[derive(Default)]
+struct LssSegment {
+    name: String,
+    // cumulative time at this split in each comparison, by comparison name
+    split_times: Vec<(String, Times)>,
+    gold: Times,
+    // time of each past attempt that finished this segment, by attempt id
+    history: Vec<(i32, Times)>,
+}
+
+#[derive(Default)]
+struct LssAttempt {
+    id: u32,
+    started: u128,
+    ended: u128,
+    pause_time: u128,
+}
+
+// everything read from the file so far
+#[derive(Default)]
+struct LssRun {
+    path: Vec<Vec<u8>>,
+    text: String,
+    times: Times,
+    // the `name` or `id` attribute of the element being read
+    key: String,
+    game_title: String,
+    category: String,
+    offset: TimeType,
+    attempt_count: u32,
+    attempts: Vec<LssAttempt>,
+    segments: Vec<LssSegment>,
+    run_id: String,
+    platform: String,
+    uses_emulator: bool,
+    region: String,
+    variables: Vec<(String, String)>,
+}
+
+impl LssRun {
+    fn open<B: BufRead>(
+        &mut self,
+        e: &BytesStart,
+        reader: &Reader<B>,
+        position: usize,
+    ) -> Result<(), ParseError> {
+        let attr = |key| attribute(e, key, reader).map_err(|e| xml_error(e, position));
+        let parent = self.path.last().map(|p| p.as_slice());
+        match (parent, e.name()) {
+            (Some(b"AttemptHistory"), b"Attempt") => {
+                let id = attr(b"id")?.unwrap_or_default();
+                let mut attempt = LssAttempt {
+                    id: parse_number(&id, position)?,
+                    ..Default::default()
+                };
+                if let Some(started) = attr(b"started")? {
+                    attempt.started = parse_date(&started, position)?;
+                }
+                if let Some(ended) = attr(b"ended")? {
+                    attempt.ended = parse_date(&ended, position)?;
+                }
+                self.attempts.push(attempt);
+                self.times = (None, None);
+            }
+            (Some(b"Segments"), b"Segment") => self.segments.push(LssSegment::default()),
+            (Some(b"SplitTimes"), b"SplitTime") | (Some(b"SegmentHistory"), b"Time") => {
+                self.key = attr(b"name")?.or(attr(b"id")?).unwrap_or_default();
+                self.times = (None, None);
+            }
+            (_, b"BestSegmentTime") => self.times = (None, None),
+            (Some(b"Metadata"), b"Run") => self.run_id = attr(b"id")?.unwrap_or_default(),
+            (Some(b"Metadata"), b"Platform") => {
+                self.uses_emulator = attr(b"usesEmulator")?.as_deref() == Some("True");
+            }
+            (Some(b"Variables"), b"Variable") => self.key = attr(b"name")?.unwrap_or_default(),
+            _ => {}
+        }
+        self.path.push(e.name().to_owned());
+        self.text.clear();
+        Ok(())
+    }
+
+    fn close(&mut self, position: usize) -> Result<(), ParseError> {
+        let name = self.path.pop().unwrap_or_default();
+        let text = std::mem::take(&mut self.text);
+        let parent = self.path.last().map(|p| p.as_slice());
+        let top_level = self.path.len() == 1;
+        match (parent, name.as_slice()) {
+            (_, b"GameName") if top_level => self.game_title = text,
+            (_, b"CategoryName") if top_level => self.category = text,
+            // mist only has offsets that start the timer below zero
+            (_, b"Offset") if top_level => {
+                self.offset = match text.strip_prefix('-') {
+                    Some(t) => parse_time(t, position)?.into(),
+                    None => TimeType::None,
+                }
+            }
+            (_, b"AttemptCount") if top_level => {
+                self.attempt_count = parse_number(&text, position)?;
+            }
+            (_, b"RealTime") => self.times.0 = parse_time(&text, position)?,
+            (_, b"GameTime") => self.times.1 = parse_time(&text, position)?,
+            (Some(b"Attempt"), b"PauseTime") => {
+                let pause_time = parse_time(&text, position)?.unwrap_or(0);
+                if let Some(a) = self.attempts.last_mut() {
+                    a.pause_time = pause_time;
+                }
+            }
+            (Some(b"Segment"), b"Name") => {
+                if let Some(s) = self.segments.last_mut() {
+                    s.name = text;
+                }
+            }
+            (Some(b"SplitTimes"), b"SplitTime") => {
+                if let Some(s) = self.segments.last_mut() {
+                    s.split_times
+                        .push((std::mem::take(&mut self.key), self.times));
+                }
+            }
+            (Some(b"Segment"), b"BestSegmentTime") => {
+                if let Some(s) = self.segments.last_mut() {
+                    s.gold = self.times;
+                }
+            }
+            (Some(b"SegmentHistory"), b"Time") => {
+                let key = std::mem::take(&mut self.key);
+                let id = key.parse::<i32>().map_err(|_| ParseError::InvalidValue {
+                    position,
+                    value: key,
+                })?;
+                if let Some(s) = self.segments.last_mut() {
+                    s.history.push((id, self.times));
+                }
+            }
+            (Some(b"Metadata"), b"Platform") => self.platform = text,
+            (Some(b"Metadata"), b"Region") => self.region = text,
+            (Some(b"Variables"), b"Variable") => {
+                self.variables.push((std::mem::take(&mut self.key), text));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // cumulative times of the comparison called `name`, as measured by `method`
+    fn split_times(&self, name: &str, method: TimingMethod) -> Vec<Option<u128>> {
+        self.segments
+            .iter()
+            .map(|s| {
+                s.split_times
+                    .iter()
+                    .find(|(n, _)| n == name)
+                    .and_then(|(_, t)| pick(*t, method))
+            })
+            .collect()
+    }
+
+    // time of each segment in the attempt with `id`, as measured by `method`
+    fn attempt_times(&self, id: i32, method: TimingMethod) -> Vec<TimeType> {
+        // runs that were never timed in game time shouldn't end up with skipped game times
+        let timed = self
+            .segments
+            .iter()
+            .flat_map(|s| &s.history)
+            .any(|(_, t)| pick(*t, method).is_some());
+        if !timed {
+            return vec![];
+        }
+        self.segments
+            .iter()
+            .enumerate()
+            .map(|(idx, s)| match s.history.iter().find(|(i, _)| *i == id) {
+                Some((_, t)) => match pick(*t, method) {
+                    // the time of the segments before it, which mist can't split back up
+                    Some(t) if self.folded(idx, id, method) => TimeType::Skipped(t),
+                    Some(t) => TimeType::Time(t),
+                    Option::None => TimeType::Skipped(0),
                 },
-                Ok(Event::Eof) => break,
-                _ => {}
+                Option::None => TimeType::None,
+            })
+            .collect()
+    }
+
+    // LiveSplit leaves skipped segments empty and adds their time to the next segment, so that
+    // segment's time in attempt `id` is not only its own if the one before it was skipped
+    fn folded(&self, idx: usize, id: i32, method: TimingMethod) -> bool {
+        idx > 0
+            && matches!(
+                self.segments[idx - 1].history.iter().find(|(i, _)| *i == id),
+                Some((_, t)) if pick(*t, method).is_none()
+            )
+    }
+
+    fn into_run(self) -> Run {
+        let mut run = Run::empty();
+        run.set_game_title(&self.game_title);
+        run.set_category(&self.category);
+        run.set_offset(self.offset);
+        run.set_splits(
+            &self
+                .segments
+                .iter()
+                .map(|s| s.name.clone())
+                .collect::<Vec<_>>(),
+        );
+        for &method in &[TimingMethod::RealTime, TimingMethod::GameTime] {
+            let pb = self.split_times(PERSONAL_BEST, method);
+            run.set_pb_of(method, pb.last().copied().flatten().into());
+            run.set_pb_times_of(method, &to_segments(&pb));
+            let golds = self
+                .segments
+                .iter()
+                .map(|s| pick(s.gold, method).into())
+                .collect::<Vec<_>>();
+            run.set_gold_times_of(method, &golds);
+            let sums = self
+                .segments
+                .iter()
+                .enumerate()
+                .map(|(idx, s)| {
+                    let times = s
+                        .history
+                        .iter()
+                        .filter(|(id, _)| !self.folded(idx, *id, method))
+                        .filter_map(|(_, t)| pick(*t, method));
+                    (times.clone().count() as u128, times.sum::<u128>().into())
+                })
+                .collect::<Vec<_>>();
+            run.set_sum_times_of(method, &sums);
+        }
+        let mut names: Vec<&str> = vec![];
+        for (name, _) in self.segments.iter().flat_map(|s| &s.split_times) {
+            if name != PERSONAL_BEST && !names.contains(&name.as_str()) {
+                names.push(name);
             }
         }
-        run.set_gold_times(&gold_times);
-        run.set_pb_times(&pb_times);
-        run.set_sum_times(&sum_times);
-        run.set_splits(&splits);
-        run.set_pb(pb.into());
-        Ok(super::sanify_run(&run))
+        let comparisons = names
+            .iter()
+            .map(|&name| {
+                CustomComparison::new(
+                    name,
+                    &to_segments(&self.split_times(name, TimingMethod::RealTime)),
+                    &to_segments(&self.split_times(name, TimingMethod::GameTime)),
+                )
+            })
+            .collect::<Vec<_>>();
+        run.set_comparisons(&comparisons);
+        let attempts = self
+            .attempts
+            .iter()
+            .map(|a| {
+                Attempt::new(
+                    a.id,
+                    a.started,
+                    a.ended,
+                    &self.attempt_times(a.id as i32, TimingMethod::RealTime),
+                    &self.attempt_times(a.id as i32, TimingMethod::GameTime),
+                    a.pause_time,
+                )
+            })
+            .collect::<Vec<_>>();
+        run.set_attempts(&attempts);
+        run.set_attempt_count(self.attempt_count);
+        run.set_metadata(Metadata::new(
+            &self.run_id,
+            &self.platform,
+            self.uses_emulator,
+            &self.region,
+            &self.variables,
+        ));
+        run
+    }
+}
+
+fn parse_time(text: &str, position: usize) -> Result<Option<u128>, ParseError> {
+    if text.is_empty() {
+        return Ok(None);
+    }
+    str_to_ns(text)
+        .map(Some)
+        .ok_or_else(|| ParseError::InvalidValue {
+            position,
+            value: text.to_owned(),
+        })
+}
+
+fn parse_date(text: &str, position: usize) -> Result<u128, ParseError> {
+    date_to_ms(text).ok_or_else(|| ParseError::InvalidValue {
+        position,
+        value: text.to_owned(),
+    })
+}
+
+fn parse_number(text: &str, position: usize) -> Result<u32, ParseError> {
+    text.trim().parse().map_err(|_| ParseError::InvalidValue {
+        position,
+        value: text.to_owned(),
+    })
+}
+
+fn write_run<W: Write>(run: &Run, w: W) -> io::Result<()> {
+    let run = super::sanify_run(run);
+    let game = TimingMethod::GameTime;
+    let len = run.splits().len();
+    let mut xml = XmlWriter { inner: w, depth: 0 };
+    xml.line(r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    xml.open("Run", &[("version", "1.7.0")])?;
+    xml.empty("GameIcon", &[])?;
+    xml.leaf("GameName", &[], run.game_title())?;
+    xml.leaf("CategoryName", &[], run.category())?;
+    xml.empty("LayoutPath", &[])?;
+    let metadata = run.metadata();
+    xml.open("Metadata", &[])?;
+    xml.empty("Run", &[("id", metadata.run_id())])?;
+    let emulator = if metadata.uses_emulator() {
+        "True"
+    } else {
+        "False"
+    };
+    xml.leaf(
+        "Platform",
+        &[("usesEmulator", emulator)],
+        metadata.platform(),
+    )?;
+    xml.leaf("Region", &[], metadata.region())?;
+    xml.open("Variables", &[])?;
+    for (name, value) in metadata.variables() {
+        xml.leaf("Variable", &[("name", name)], value)?;
+    }
+    xml.close("Variables")?;
+    xml.close("Metadata")?;
+    let offset = match run.offset() {
+        TimeType::Time(t) => format!("-{}", ns_to_str(t)),
+        _ => ns_to_str(0),
+    };
+    xml.leaf("Offset", &[], &offset)?;
+    xml.leaf("AttemptCount", &[], &run.attempt_count().to_string())?;
+    xml.open("AttemptHistory", &[])?;
+    for a in run.attempts() {
+        let (id, started, ended) = (
+            a.id().to_string(),
+            ms_to_date(a.started()),
+            ms_to_date(a.ended()),
+        );
+        let attrs = [
+            ("id", id.as_str()),
+            ("started", &started),
+            ("isStartedSynced", "True"),
+            ("ended", &ended),
+            ("isEndedSynced", "True"),
+        ];
+        let game_time = if a.times_of(game).iter().any(|t| t.is_time()) {
+            a.time_of(game).to_option()
+        } else {
+            None
+        };
+        if !a.is_finished() && a.pause_time() == 0 {
+            xml.empty("Attempt", &attrs)?;
+            continue;
+        }
+        xml.open("Attempt", &attrs)?;
+        xml.time_leaves((a.time().to_option(), game_time))?;
+        if a.pause_time() > 0 {
+            xml.leaf("PauseTime", &[], &ns_to_str(a.pause_time()))?;
+        }
+        xml.close("Attempt")?;
+    }
+    xml.close("AttemptHistory")?;
+    let pb = (
+        to_cumulative(run.pb_times(), len),
+        to_cumulative(run.pb_times_of(game), len),
+    );
+    let comparisons = run
+        .comparisons()
+        .iter()
+        .map(|c| {
+            (
+                c.name(),
+                to_cumulative(c.times(), len),
+                to_cumulative(c.times_of(game), len),
+            )
+        })
+        .collect::<Vec<_>>();
+    let history = segment_history(&run);
+    xml.open("Segments", &[])?;
+    for i in 0..len {
+        xml.open("Segment", &[])?;
+        xml.leaf("Name", &[], &run.splits()[i])?;
+        xml.empty("Icon", &[])?;
+        xml.open("SplitTimes", &[])?;
+        xml.times("SplitTime", &[("name", PERSONAL_BEST)], (pb.0[i], pb.1[i]))?;
+        for (name, real, game) in &comparisons {
+            xml.times("SplitTime", &[("name", name)], (real[i], game[i]))?;
+        }
+        xml.close("SplitTimes")?;
+        let gold = (
+            run.gold_times()[i].to_option(),
+            run.gold_times_of(game)[i].to_option(),
+        );
+        xml.times("BestSegmentTime", &[], gold)?;
+        xml.open("SegmentHistory", &[])?;
+        for (id, times) in &history[i] {
+            xml.times("Time", &[("id", &id.to_string())], *times)?;
+        }
+        xml.close("SegmentHistory")?;
+        xml.close("Segment")?;
+    }
+    xml.close("Segments")?;
+    xml.empty("AutoSplitterSettings", &[])?;
+    xml.close("Run")?;
+    xml.inner.flush()
+}
+
+// time of each segment in each attempt, by attempt id. like LiveSplit, skipped segments have no
+// time and the time spent on them is added to the next segment that is written
+fn segment_history(run: &Run) -> Vec<Vec<(u32, Times)>> {
+    let len = run.splits().len();
+    let mut history = vec![vec![]; len];
+    for a in run.attempts() {
+        let mut skipped = ((0, false), (0, false));
+        let real = a.times().iter();
+        let game = a.times_of(TimingMethod::GameTime).iter();
+        for (i, (&real, &game)) in real.zip(game).enumerate().take(len) {
+            if real.is_none() && game.is_none() {
+                continue;
+            }
+            let real = with_skipped(real, &mut skipped.0);
+            let game = with_skipped(game, &mut skipped.1);
+            history[i].push((a.id(), (real, game)));
+        }
+    }
+    history
+}
+
+// `skipped` is the time carried over from skipped segments, and whether the last segment was
+// skipped with no time. that only happens in runs imported from LiveSplit, where the next skipped
+// segment holds the time of both, see `LssRun::folded`
+fn with_skipped(time: TimeType, skipped: &mut (u128, bool)) -> Option<u128> {
+    let folded = std::mem::take(&mut skipped.1);
+    match time {
+        TimeType::Time(t) => Some(t + std::mem::take(&mut skipped.0)),
+        TimeType::Skipped(0) => {
+            skipped.1 = true;
+            None
+        }
+        TimeType::Skipped(t) if folded => Some(t + std::mem::take(&mut skipped.0)),
+        TimeType::Skipped(t) => {
+            skipped.0 += t;
+            None
+        }
+        TimeType::None => None,
+    }
+}
+
+// writes indented xml, escaping text and attribute values
+struct XmlWriter<W> {
+    inner: W,
+    depth: usize,
+}
+
+impl<W: Write> XmlWriter<W> {
+    fn line(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.inner, "{:width$}{}", "", line, width = self.depth * 2)
+    }
+    fn open(&mut self, tag: &str, attrs: &[(&str, &str)]) -> io::Result<()> {
+        self.line(&format!("<{}{}>", tag, attributes(attrs)))?;
+        self.depth += 1;
+        Ok(())
+    }
+    fn close(&mut self, tag: &str) -> io::Result<()> {
+        self.depth -= 1;
+        self.line(&format!("</{}>", tag))
+    }
+    fn empty(&mut self, tag: &str, attrs: &[(&str, &str)]) -> io::Result<()> {
+        self.line(&format!("<{}{} />", tag, attributes(attrs)))
+    }
+    fn leaf(&mut self, tag: &str, attrs: &[(&str, &str)], text: &str) -> io::Result<()> {
+        self.line(&format!(
+            "<{0}{1}>{2}</{0}>",
+            tag,
+            attributes(attrs),
+            escape(text)
+        ))
+    }
+    fn time_leaves(&mut self, times: Times) -> io::Result<()> {
+        if let Some(t) = times.0 {
+            self.leaf("RealTime", &[], &ns_to_str(t))?;
+        }
+        if let Some(t) = times.1 {
+            self.leaf("GameTime", &[], &ns_to_str(t))?;
+        }
+        Ok(())
+    }
+    // an element holding a real time and a game time, left empty if there are neither
+    fn times(&mut self, tag: &str, attrs: &[(&str, &str)], times: Times) -> io::Result<()> {
+        if times == (None, None) {
+            return self.empty(tag, attrs);
+        }
+        self.open(tag, attrs)?;
+        self.time_leaves(times)?;
+        self.close(tag)
+    }
+}
+
+fn escape(s: &str) -> String {
+    String::from_utf8_lossy(&quick_xml::escape::escape(s.as_bytes())).into_owned()
+}
+
+fn attributes(attrs: &[(&str, &str)]) -> String {
+    attrs
+        .iter()
+        .map(|(key, value)| format!(" {}=\"{}\"", key, escape(value)))
+        .collect()
+}
+
+fn pick(times: Times, method: TimingMethod) -> Option<u128> {
+    match method {
+        TimingMethod::RealTime => times.0,
+        TimingMethod::GameTime => times.1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::TimeType::*;
+
+    const SECS: u128 = 1_000_000_000;
+
+    const LSS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Run version="1.7.0">
+  <GameIcon />
+  <GameName>Game</GameName>
+  <CategoryName>Any%</CategoryName>
+  <Metadata>
+    <Run id="abc123" />
+    <Platform usesEmulator="True">PC</Platform>
+    <Region>USA</Region>
+    <Variables>
+      <Variable name="Difficulty">Hard</Variable>
+    </Variables>
+  </Metadata>
+  <Offset>-00:00:01.5000000</Offset>
+  <AttemptCount>5</AttemptCount>
+  <AttemptHistory>
+    <Attempt id="1" started="01/02/2021 03:04:05" isStartedSynced="True" ended="01/02/2021 03:05:05" isEndedSynced="True">
+      <RealTime>00:01:00</RealTime>
+      <PauseTime>00:00:02.5000000</PauseTime>
+    </Attempt>
+    <Attempt id="2" started="01/03/2021 00:00:00" ended="01/03/2021 00:00:30" />
+  </AttemptHistory>
+  <Segments>
+    <Segment>
+      <Name>first</Name>
+      <SplitTimes>
+        <SplitTime name="Personal Best">
+          <RealTime>00:00:20.2500000</RealTime>
+          <GameTime>00:00:18</GameTime>
+        </SplitTime>
+        <SplitTime name="Friend">
+          <RealTime>00:00:10</RealTime>
+        </SplitTime>
+      </SplitTimes>
+      <BestSegmentTime>
+        <RealTime>00:00:19</RealTime>
+        <GameTime>00:00:17</GameTime>
+      </BestSegmentTime>
+      <SegmentHistory>
+        <Time id="1">
+          <RealTime>00:00:20.2500000</RealTime>
+          <GameTime>00:00:18</GameTime>
+        </Time>
+        <Time id="2" />
+        <Time id="-1">
+          <RealTime>00:00:19</RealTime>
+        </Time>
+      </SegmentHistory>
+    </Segment>
+    <Segment>
+      <Name>second &amp; last</Name>
+      <SplitTimes>
+        <SplitTime name="Personal Best">
+          <RealTime>100:00:00</RealTime>
+          <GameTime>1.00:00:00</GameTime>
+        </SplitTime>
+        <SplitTime name="Friend" />
+      </SplitTimes>
+      <BestSegmentTime />
+      <SegmentHistory>
+        <Time id="1">
+          <RealTime>00:00:39.7500000</RealTime>
+          <GameTime>00:00:40</GameTime>
+        </Time>
+      </SegmentHistory>
+    </Segment>
+  </Segments>
+  <AutoSplitterSettings>
+    <Name>not a split</Name>
+  </AutoSplitterSettings>
+</Run>"#;
+
+    #[test]
+    fn test_str_to_ns() {
+        assert_eq!(str_to_ns("00:00:01"), Some(SECS));
+        assert_eq!(str_to_ns("00:01:02.5"), Some(62 * SECS + SECS / 2));
+        assert_eq!(str_to_ns("00:00:00.1234567"), Some(123_456_700));
+        assert_eq!(str_to_ns("123:00:00"), Some(123 * 3600 * SECS));
+        assert_eq!(str_to_ns("2.01:00:00"), Some(49 * 3600 * SECS));
+        assert_eq!(str_to_ns("00:60:00"), Option::None);
+        assert_eq!(str_to_ns("1:2"), Option::None);
+        assert_eq!(str_to_ns("00:00:0x"), Option::None);
+        assert_eq!(str_to_ns("-00:00:01"), Option::None);
+        assert_eq!(date_to_ms("01/01/1970 00:00:01"), Some(1000));
+        assert_eq!(date_to_ms("03/01/2000 00:00:00"), Some(951_868_800_000));
+        assert_eq!(date_to_ms("13/01/2000 00:00:00"), Option::None);
+    }
+
+    #[test]
+    fn test_parse() {
+        let run = parse_reader(LSS.as_bytes()).unwrap();
+        assert_eq!(run.game_title(), "Game");
+        assert_eq!(run.category(), "Any%");
+        assert_eq!(run.offset(), Time(3 * SECS / 2));
+        assert_eq!(run.splits(), &vec!["first", "second & last"]);
+        assert_eq!(run.pb(), Time(100 * 3600 * SECS));
+        assert_eq!(
+            run.pb_times(),
+            &vec![
+                Time(20 * SECS + SECS / 4),
+                Time(100 * 3600 * SECS - 20 * SECS - SECS / 4)
+            ]
+        );
+        let game = TimingMethod::GameTime;
+        assert_eq!(run.pb_of(game), Time(24 * 3600 * SECS));
+        assert_eq!(run.gold_times_of(game), &vec![Time(17 * SECS), None]);
+        assert_eq!(run.gold_times(), &vec![Time(19 * SECS), None]);
+        assert_eq!(
+            run.sum_times(),
+            &vec![
+                (2, Time(39 * SECS + SECS / 4)),
+                (1, Time(39 * SECS + 3 * SECS / 4))
+            ]
+        );
+        assert_eq!(run.comparisons().len(), 1);
+        assert_eq!(run.comparisons()[0].name(), "Friend");
+        assert_eq!(run.comparisons()[0].times(), &vec![Time(10 * SECS), None]);
+        let attempts = run.attempts();
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(attempts[0].started(), 1_609_556_645_000);
+        assert_eq!(attempts[0].ended(), 1_609_556_705_000);
+        assert_eq!(attempts[0].pause_time(), 5 * SECS / 2);
+        assert_eq!(attempts[0].time(), Time(60 * SECS));
+        assert_eq!(attempts[0].time_of(game), Time(58 * SECS));
+        assert_eq!(attempts[1].times(), &vec![Skipped(0), None]);
+        assert_eq!(attempts[1].times_of(game), &vec![Skipped(0), None]);
+        assert_eq!(run.attempt_count(), 5);
+        assert_eq!(run.next_attempt_id(), 6);
+        let metadata = run.metadata();
+        assert_eq!(metadata.run_id(), "abc123");
+        assert_eq!(metadata.platform(), "PC");
+        assert!(metadata.uses_emulator());
+        assert_eq!(metadata.region(), "USA");
+        assert_eq!(
+            metadata.variables(),
+            &vec![("Difficulty".to_owned(), "Hard".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_skipped_history() {
+        let lss = r#"<Run>
+  <AttemptHistory>
+    <Attempt id="1" started="01/02/2021 03:04:05" ended="01/02/2021 03:05:05">
+      <RealTime>00:01:00</RealTime>
+    </Attempt>
+  </AttemptHistory>
+  <Segments>
+    <Segment>
+      <Name>a</Name>
+      <SegmentHistory>
+        <Time id="1"><RealTime>00:00:10</RealTime></Time>
+        <Time id="-1"><RealTime>00:00:12</RealTime></Time>
+      </SegmentHistory>
+    </Segment>
+    <Segment>
+      <Name>b</Name>
+      <SegmentHistory>
+        <Time id="1" />
+        <Time id="-1"><RealTime>00:00:20</RealTime></Time>
+      </SegmentHistory>
+    </Segment>
+    <Segment>
+      <Name>c</Name>
+      <SegmentHistory>
+        <Time id="1"><RealTime>00:00:50</RealTime></Time>
+        <Time id="-1"><RealTime>00:00:30</RealTime></Time>
+      </SegmentHistory>
+    </Segment>
+  </Segments>
+</Run>"#;
+        let run = parse_reader(lss.as_bytes()).unwrap();
+        let attempt = &run.attempts()[0];
+        // the 50 seconds of c include the time spent on b
+        assert_eq!(
+            attempt.times(),
+            &vec![Time(10 * SECS), Skipped(0), Skipped(50 * SECS)]
+        );
+        assert_eq!(attempt.time(), Time(60 * SECS));
+        assert_eq!(
+            run.sum_times(),
+            &vec![
+                (2, Time(22 * SECS)),
+                (1, Time(20 * SECS)),
+                (1, Time(30 * SECS))
+            ]
+        );
+        let mut written = vec![];
+        write_run(&run, &mut written).unwrap();
+        let written = parse_reader(written.as_slice()).unwrap();
+        assert_eq!(written.attempts(), run.attempts());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for &t in &[
+            0,
+            SECS,
+            123_456_700,
+            123 * 3600 * SECS,
+            49 * 3600 * SECS + 5,
+        ] {
+            assert_eq!(str_to_ns(&ns_to_str(t)), Some(t / 100 * 100));
+        }
+        for &t in &[0, 951_868_800_000, 1_609_556_645_000, 4_102_444_799_000] {
+            assert_eq!(date_to_ms(&ms_to_date(t)), Some(t));
+        }
+        // segment history from outside the attempt history is only kept as part of the sum times
+        let lss = LSS.replace(
+            r#"<Time id="-1">
+          <RealTime>00:00:19</RealTime>
+        </Time>"#,
+            "",
+        );
+        assert_ne!(lss, LSS);
+        let run = parse_reader(lss.as_bytes()).unwrap();
+        let mut written = vec![];
+        write_run(&run, &mut written).unwrap();
+        assert_eq!(parse_reader(written.as_slice()).unwrap(), run);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let bad_time = LSS.replace("00:00:19<", "00:0a:19<");
+        assert!(matches!(
+            parse_reader(bad_time.as_bytes()),
+            Err(ParseError::InvalidValue { ref value, .. }) if value == "00:0a:19"
+        ));
+        let bad_date = LSS.replace("01/03/2021", "2021-01-03");
+        assert!(matches!(
+            parse_reader(bad_date.as_bytes()),
+            Err(ParseError::InvalidValue { .. })
+        ));
+        let bad_count = LSS.replace("<AttemptCount>5", "<AttemptCount>five");
+        assert!(matches!(
+            parse_reader(bad_count.as_bytes()),
+            Err(ParseError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            parse_reader("<Run><GameName>a &bogus; b</GameName></Run>".as_bytes()),
+            Err(ParseError::Xml { .. })
+        ));
     }
 }
@@ -7,25 +7,75 @@ mod lss;
 #[cfg(feature = "lss")]
 pub use lss::LssParser;
 
-use crate::timer::Run;
+use crate::timer::{Attempt, Run, TimingMethod};
 
 pub(crate) fn sanify_run(run: &Run) -> Run {
     let mut run = run.clone();
     let len = run.splits().len();
-    let mut golds = run.gold_times().to_owned();
-    let mut times = run.pb_times().to_owned();
-    let mut sums = run.sum_times().to_owned();
-    if golds.len() != len {
-        golds.resize_with(len, Default::default);
-        run.set_gold_times(&golds);
+    for &method in &[TimingMethod::RealTime, TimingMethod::GameTime] {
+        let mut golds = run.gold_times_of(method).to_owned();
+        let mut times = run.pb_times_of(method).to_owned();
+        let mut sums = run.sum_times_of(method).to_owned();
+        if golds.len() != len {
+            golds.resize_with(len, Default::default);
+            run.set_gold_times_of(method, &golds);
+        }
+        if times.len() != len {
+            times.resize_with(len, Default::default);
+            run.set_pb_times_of(method, &times);
+        }
+        if sums.len() != len {
+            sums.resize_with(len, Default::default);
+            run.set_sum_times_of(method, &sums);
+        }
     }
-    if times.len() != len {
-        times.resize_with(len, Default::default);
-        run.set_pb_times(&times);
+    let game = TimingMethod::GameTime;
+    if run
+        .attempts()
+        .iter()
+        .any(|a| a.times().len() != len || a.times_of(game).len() != len)
+    {
+        let attempts = run
+            .attempts()
+            .iter()
+            .map(|a| {
+                let mut times = a.times().to_owned();
+                times.resize_with(len, Default::default);
+                let mut game_times = a.times_of(game).to_owned();
+                game_times.resize_with(len, Default::default);
+                Attempt::new(
+                    a.id(),
+                    a.started(),
+                    a.ended(),
+                    &times,
+                    &game_times,
+                    a.pause_time(),
+                )
+            })
+            .collect::<Vec<_>>();
+        run.set_attempts(&attempts);
     }
-    if sums.len() != len {
-        sums.resize_with(len, Default::default);
-        run.set_sum_times(&sums);
+    if !run.notes().is_empty() && run.notes().len() != len {
+        let mut notes = run.notes().to_owned();
+        notes.resize_with(len, Default::default);
+        run.set_notes(&notes);
+    }
+    // groups have to be in order, not overlap, and only contain splits that exist
+    let mut next = 0;
+    let groups = run
+        .groups()
+        .iter()
+        .filter(|g| {
+            let valid = g.start() >= next && g.start() <= g.end() && g.end() < len;
+            if valid {
+                next = g.end() + 1;
+            }
+            valid
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    if groups.len() != run.groups().len() {
+        run.set_groups(&groups);
     }
     run
 }
@@ -1,4 +1,5 @@
-use crate::timer::Run;
+use crate::error::ParseError;
+use crate::timer::{Categories, Run, TimeType};
 use ron::{
     de::from_str,
     ser::{to_writer_pretty, PrettyConfig},
@@ -7,9 +8,11 @@ use serde::Deserialize;
 use std::{
     fs::File,
     io::{BufRead, BufReader, Read, Write},
-    str::FromStr,
 };
 
+// newest msf version this version of mist can read
+const LATEST_VERSION: u32 = 5;
+
 #[derive(Deserialize)]
 struct LegacyRun {
     game_title: String,
@@ -33,6 +36,18 @@ struct RunV1 {
     sum_times: Vec<(u128, u128)>,
 }
 
+#[derive(Deserialize)]
+struct RunV2 {
+    game_title: String,
+    category: String,
+    offset: TimeType,
+    pb: TimeType,
+    splits: Vec<String>,
+    pb_times: Vec<TimeType>,
+    gold_times: Vec<TimeType>,
+    sum_times: Vec<(u128, TimeType)>,
+}
+
 impl From<LegacyRun> for Run {
     fn from(r: LegacyRun) -> Run {
         Run::new(
@@ -69,6 +84,21 @@ impl From<RunV1> for Run {
     }
 }
 
+impl From<RunV2> for Run {
+    fn from(r: RunV2) -> Run {
+        Run::new(
+            r.game_title,
+            r.category,
+            r.offset,
+            r.pb,
+            &r.splits,
+            &r.pb_times,
+            &r.gold_times,
+            &r.sum_times,
+        )
+    }
+}
+
 /// Parses the version and [`Run`] from a mist split file (msf).
 pub struct MsfParser {
     filename: String,
@@ -89,48 +119,109 @@ impl MsfParser {
     ///
     /// # Errors
     ///
-    /// * If the file cannot be read from or is empty.
-    /// * If a [`Run`] (legacy or otherwise) cannot be parsed from the file.
-    pub fn parse(&self) -> Result<Run, String> {
-        let f = File::open(&self.filename).map_err(|e| e.to_string())?;
+    /// * [`ParseError::Io`] if the file cannot be read from.
+    /// * [`ParseError::Empty`] if the file is empty.
+    /// * [`ParseError::UnsupportedVersion`] if the file is from a newer version of mist.
+    /// * [`ParseError::Syntax`] if a [`Run`] (legacy or otherwise) cannot be parsed from the file.
+    ///
+    /// If the file holds several categories, the selected one is returned.
+    pub fn parse(&self) -> Result<Run, ParseError> {
+        let f = File::open(&self.filename)?;
         self.parse_impl(BufReader::new(f))
     }
 
-    fn parse_impl<R: Read>(&self, reader: BufReader<R>) -> Result<Run, String> {
-        let mut lines = reader.lines().map(|l| l.unwrap());
-        // TODO: better error handling
-        let ver_info = String::from_str(&lines.next().ok_or("Input was empty.")?).unwrap();
-        let version: u32 = match ver_info.rsplit_once(' ') {
+    /// Attempt to parse every category from the file stored in the [`MsfParser`].
+    ///
+    /// Files with a single [`Run`] give [`Categories`] holding only that run.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`parse`](MsfParser::parse).
+    pub fn parse_categories(&self) -> Result<Categories, ParseError> {
+        let f = File::open(&self.filename)?;
+        self.parse_categories_impl(BufReader::new(f))
+    }
+
+    fn parse_impl<R: Read>(&self, reader: BufReader<R>) -> Result<Run, ParseError> {
+        Ok(self.parse_categories_impl(reader)?.into_selected())
+    }
+
+    fn parse_categories_impl<R: Read>(
+        &self,
+        mut reader: BufReader<R>,
+    ) -> Result<Categories, ParseError> {
+        let mut ver_info = String::new();
+        if reader.read_line(&mut ver_info)? == 0 {
+            return Err(ParseError::Empty);
+        }
+        let version: u32 = match ver_info.trim_end().rsplit_once(' ') {
             Some(num) => num.1.parse::<u32>().unwrap_or(0),
             None => 0,
         };
-        let data = {
-            let mut s = String::new();
-            if version == 0 {
-                s.push_str(&ver_info);
-            }
-            for line in lines {
-                s.push_str(&line);
-                s.push('\n');
-            }
-            s
+        if version > LATEST_VERSION {
+            return Err(ParseError::UnsupportedVersion(version));
+        }
+        // keep the version line as a blank one so that error locations match the file
+        let mut data = if version == 0 {
+            ver_info
+        } else {
+            String::from("\n")
         };
-        let run = match version {
-            1 => from_str::<RunV1>(&data).map_err(|e| e.to_string())?.into(),
-            2 => from_str::<Run>(&data).map_err(|e| e.to_string())?,
+        reader.read_to_string(&mut data)?;
+        if version == 5 {
+            let cats = from_str::<Categories>(&data).map_err(ParseError::syntax)?;
+            let runs = cats
+                .runs()
+                .iter()
+                .map(super::sanify_run)
+                .collect::<Vec<_>>();
+            return Ok(Categories::new(&runs, cats.selected()));
+        }
+        let mut run = match version {
+            1 => from_str::<RunV1>(&data).map_err(ParseError::syntax)?.into(),
+            2 => from_str::<RunV2>(&data).map_err(ParseError::syntax)?.into(),
+            3 | 4 => from_str::<Run>(&data).map_err(ParseError::syntax)?,
             _ => from_str::<LegacyRun>(&data)
-                .map_err(|e| e.to_string())?
+                .map_err(ParseError::syntax)?
                 .into(),
         };
-        Ok(super::sanify_run(&run))
+        // version 4 changed times from milliseconds to nanoseconds
+        if version < 4 {
+            run.ms_to_ns();
+        }
+        Ok(super::sanify_run(&run).into())
     }
 
     /// Write the given run to the file stored in the [`MsfParser`].
-    pub fn write(&mut self, run: &Run) -> Result<(), String> {
+    ///
+    /// # Errors
+    ///
+    /// * [`ParseError::Io`] if the file cannot be written to.
+    /// * [`ParseError::Serialize`] if the run cannot be serialized.
+    pub fn write(&mut self, run: &Run) -> Result<(), ParseError> {
         let run = super::sanify_run(run);
-        let mut file = File::create(&self.filename).map_err(|e| e.to_string())?;
-        file.write(b"version 2\n").map_err(|e| e.to_string())?;
-        to_writer_pretty(&mut file, &run, PrettyConfig::new()).map_err(|e| e.to_string())?;
+        let mut file = File::create(&self.filename)?;
+        file.write_all(b"version 4\n")?;
+        to_writer_pretty(&mut file, &run, PrettyConfig::new()).map_err(ParseError::Serialize)?;
+        Ok(())
+    }
+
+    /// Write every category to the file stored in the [`MsfParser`].
+    ///
+    /// If there is only one category it is written the same way as [`write`](MsfParser::write).
+    pub fn write_categories(&mut self, cats: &Categories) -> Result<(), ParseError> {
+        if cats.len() == 1 {
+            return self.write(cats.selected_run());
+        }
+        let runs = cats
+            .runs()
+            .iter()
+            .map(super::sanify_run)
+            .collect::<Vec<_>>();
+        let cats = Categories::new(&runs, cats.selected());
+        let mut file = File::create(&self.filename)?;
+        file.write_all(b"version 5\n")?;
+        to_writer_pretty(&mut file, &cats, PrettyConfig::new()).map_err(ParseError::Serialize)?;
         Ok(())
     }
 
@@ -152,7 +243,257 @@ impl MsfParser {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::timer::TimeType::{self, *};
+    use crate::timer::{
+        Attempt, CustomComparison,
+        TimeType::{self, *},
+    };
+
+    const CATEGORIES_RUN: &[u8] = b"version 5\n
+        (
+            selected: 1,
+            runs: [
+                (
+                    game_title: \"test\",
+                    category: \"any%\",
+                    offset: None,
+                    pb: None,
+                    splits: [\"a\", \"b\"],
+                    pb_times: [None, None],
+                    gold_times: [None, None],
+                    sum_times: [(0, None), (0, None)],
+                    attempts: [],
+                ),
+                (
+                    game_title: \"test\",
+                    category: \"100%\",
+                    offset: None,
+                    pb: Time(300),
+                    splits: [\"a\", \"b\", \"c\"],
+                    pb_times: [Time(100), Time(100), Time(100)],
+                    gold_times: [Time(100), Time(100), Time(100)],
+                    sum_times: [(1, Time(100)), (1, Time(100)), (1, Time(100))],
+                    attempts: [],
+                ),
+            ],
+        )";
+
+    #[test]
+    fn test_parse_categories() {
+        let parser = MsfParser::new(String::new());
+        let cats = parser
+            .parse_categories_impl(std::io::BufReader::new(CATEGORIES_RUN))
+            .unwrap();
+        assert_eq!(cats.len(), 2);
+        assert_eq!(cats.selected(), 1);
+        assert_eq!(cats.runs()[0].category(), "any%");
+        assert_eq!(cats.selected_run().pb(), Time(300));
+        // parsing a single run gives the selected one
+        let run = parser
+            .parse_impl(std::io::BufReader::new(CATEGORIES_RUN))
+            .unwrap();
+        assert_eq!(run.category(), "100%");
+        // a plain run file is a single category
+        let cats = parser
+            .parse_categories_impl(std::io::BufReader::new(NOTES_RUN))
+            .unwrap();
+        assert_eq!(cats.len(), 1);
+        assert_eq!(cats.selected_run().splits().len(), 3);
+    }
+
+    // runs from before version 4 are in milliseconds
+    fn ms(mut run: Run) -> Run {
+        run.ms_to_ns();
+        run
+    }
+
+    const V3_RUN: &[u8] = b"version 3\n
+        (
+            game_title: \"test\",
+            category: \"test\",
+            offset: Time(200),
+            pb: Time(1234),
+            splits: [\"test\", \"test2\"],
+            pb_times: [Time(1000), Time(234)],
+            gold_times: [Time(1000), Time(234)],
+            sum_times: [(2, Time(2100)), (1, Time(234))],
+            attempts: [
+                (id: 1, started: 1000, ended: 2500, times: [Time(1100), None], pause_time: 400),
+                (id: 2, started: 3000, ended: 4234, times: [Time(1000), Time(234)], pause_time: 0),
+            ],
+        )";
+
+    #[test]
+    fn test_parse_v3() {
+        let reader = std::io::BufReader::new(V3_RUN);
+        let parser = MsfParser::new(String::new());
+        let run = parser.parse_impl(reader).unwrap();
+        let mut expected = Run::new(
+            "test",
+            "test",
+            Time(200),
+            Time(1234),
+            &["test".into(), "test2".into()],
+            &[Time(1000), Time(234)],
+            &[Time(1000), Time(234)],
+            &[(2, Time(2100)), (1, Time(234))],
+        );
+        expected.set_attempts(&[
+            Attempt::new(
+                1,
+                1000,
+                2500,
+                &[Time(1100), TimeType::None],
+                &[TimeType::None, TimeType::None],
+                400,
+            ),
+            Attempt::new(
+                2,
+                3000,
+                4234,
+                &[Time(1000), Time(234)],
+                &[TimeType::None, TimeType::None],
+                0,
+            ),
+        ]);
+        assert_eq!(run, ms(expected));
+        assert!(!run.attempts()[0].is_finished());
+        assert_eq!(run.attempts()[1].time(), Time(1_234_000_000));
+        // timestamps are still in milliseconds
+        assert_eq!(run.attempts()[1].started(), 3000);
+        assert_eq!(run.attempts()[0].pause_time(), 400_000_000);
+        assert_eq!(run.next_attempt_id(), 3);
+    }
+
+    const GAME_TIME_RUN: &[u8] = b"version 4\n
+        (
+            game_title: \"test\",
+            category: \"test\",
+            offset: None,
+            pb: Time(1234),
+            splits: [\"test\"],
+            pb_times: [Time(1234)],
+            gold_times: [Time(1234)],
+            sum_times: [(1, Time(1234))],
+            game_pb: Time(1000),
+            game_pb_times: [Time(1000)],
+            game_gold_times: [Time(900)],
+            game_sum_times: [(1, Time(1000))],
+            attempts: [],
+        )";
+
+    #[test]
+    fn test_parse_game_time() {
+        use crate::timer::TimingMethod::*;
+        let reader = std::io::BufReader::new(GAME_TIME_RUN);
+        let parser = MsfParser::new(String::new());
+        let run = parser.parse_impl(reader).unwrap();
+        assert_eq!(run.pb_of(RealTime), Time(1234));
+        assert_eq!(run.pb_of(GameTime), Time(1000));
+        assert_eq!(run.pb_times_of(GameTime), &vec![Time(1000)]);
+        assert_eq!(run.gold_times_of(GameTime), &vec![Time(900)]);
+        assert_eq!(run.sum_times_of(GameTime), &vec![(1, Time(1000))]);
+    }
+
+    const CUSTOM_COMPARISON_RUN: &[u8] = b"version 4\n
+        (
+            game_title: \"test\",
+            category: \"test\",
+            offset: None,
+            pb: Time(1234),
+            splits: [\"test\", \"test2\"],
+            pb_times: [Time(1000), Time(234)],
+            gold_times: [Time(1000), Time(234)],
+            sum_times: [(1, Time(1000)), (1, Time(234))],
+            attempts: [],
+            comparisons: [
+                (
+                    name: \"friend\",
+                    times: [Time(900), Time(200)],
+                ),
+            ],
+        )";
+
+    #[test]
+    fn test_parse_custom_comparisons() {
+        use crate::timer::TimingMethod::*;
+        let reader = std::io::BufReader::new(CUSTOM_COMPARISON_RUN);
+        let parser = MsfParser::new(String::new());
+        let run = parser.parse_impl(reader).unwrap();
+        assert_eq!(
+            run.comparisons(),
+            &vec![CustomComparison::new(
+                "friend",
+                &[Time(900), Time(200)],
+                &[]
+            )]
+        );
+        assert_eq!(run.comparisons()[0].times_of(RealTime).len(), 2);
+        assert!(run.comparisons()[0].times_of(GameTime).is_empty());
+    }
+
+    const GROUPS_RUN: &[u8] = b"version 4\n
+        (
+            game_title: \"test\",
+            category: \"test\",
+            offset: None,
+            pb: Time(600),
+            splits: [\"1-1\", \"1-2\", \"2-1\", \"2-2\"],
+            pb_times: [Time(100), Time(200), Time(150), Time(150)],
+            gold_times: [Time(100), Time(200), Time(150), Time(150)],
+            sum_times: [(1, Time(100)), (1, Time(200)), (1, Time(150)), (1, Time(150))],
+            attempts: [],
+            groups: [
+                (name: \"world 1\", start: 0, end: 1, gold_time: Time(280)),
+                (name: \"overlapping\", start: 1, end: 2),
+                (name: \"world 2\", start: 2, end: 3),
+                (name: \"too long\", start: 4, end: 6),
+            ],
+        )";
+
+    #[test]
+    fn test_parse_groups() {
+        use crate::timer::{SegmentGroup, TimingMethod::*};
+        let reader = std::io::BufReader::new(GROUPS_RUN);
+        let parser = MsfParser::new(String::new());
+        let run = parser.parse_impl(reader).unwrap();
+        let mut world_1 = SegmentGroup::new("world 1", 0, 1);
+        world_1.set_gold_time_of(RealTime, Time(280));
+        assert_eq!(
+            run.groups(),
+            &vec![world_1, SegmentGroup::new("world 2", 2, 3)]
+        );
+        assert_eq!(run.group_of(3), Some(1));
+        assert_eq!(run.group_pb_of(0, RealTime), Time(300));
+        assert_eq!(run.group_pb_of(1, GameTime), TimeType::None);
+    }
+
+    const NOTES_RUN: &[u8] = b"version 4\n
+        (
+            game_title: \"test\",
+            category: \"test\",
+            offset: None,
+            pb: Time(300),
+            splits: [\"a\", \"b\", \"c\"],
+            pb_times: [Time(100), Time(100), Time(100)],
+            gold_times: [Time(100), Time(100), Time(100)],
+            sum_times: [(1, Time(100)), (1, Time(100)), (1, Time(100))],
+            attempts: [],
+            notes: [\"jump at the wall\\ndon't miss the clip\", \"\"],
+        )";
+
+    #[test]
+    fn test_parse_notes() {
+        let reader = std::io::BufReader::new(NOTES_RUN);
+        let parser = MsfParser::new(String::new());
+        let run = parser.parse_impl(reader).unwrap();
+        assert_eq!(run.notes().len(), 3);
+        assert_eq!(
+            run.split_notes(0),
+            Some("jump at the wall\ndon't miss the clip")
+        );
+        assert_eq!(run.split_notes(1), Option::None);
+        assert_eq!(run.split_notes(2), Option::None);
+    }
 
     const V2_RUN: &[u8] = b"version 2\n
         (
@@ -173,7 +514,7 @@ mod tests {
         let run = parser.parse_impl(reader).unwrap();
         assert_eq!(
             run,
-            Run::new(
+            ms(Run::new(
                 "test",
                 "test",
                 Time(200),
@@ -182,7 +523,7 @@ mod tests {
                 &[Skipped(1234)],
                 &[Time(1234)],
                 &[(2, TimeType::None)]
-            )
+            ))
         );
     }
 
@@ -205,7 +546,7 @@ mod tests {
         let run = parser.parse_impl(reader).unwrap();
         assert_eq!(
             run,
-            Run::new(
+            ms(Run::new(
                 "test",
                 "test",
                 Time(200),
@@ -214,7 +555,7 @@ mod tests {
                 &[Time(1234)],
                 &[Time(1234)],
                 &[(2, Time(2480))]
-            )
+            ))
         );
     }
 
@@ -235,7 +576,7 @@ mod tests {
         let run = parser.parse_impl(reader).unwrap();
         assert_eq!(
             run,
-            Run::new(
+            ms(Run::new(
                 "test",
                 "test",
                 Time(200),
@@ -244,7 +585,7 @@ mod tests {
                 &[Time(1234)],
                 &[Time(1234)],
                 &[(1, Time(1234))]
-            )
+            ))
         );
     }
 
@@ -268,7 +609,7 @@ mod tests {
         let run = crate::parse::sanify_run(&run);
         assert_eq!(
             run,
-            Run::new(
+            ms(Run::new(
                 "test",
                 "test",
                 Time(200),
@@ -277,7 +618,23 @@ mod tests {
                 &[Time(1234), TimeType::None],
                 &[Time(1234), TimeType::None],
                 &[(2, Time(1234)), (1, Time(1243))]
-            )
+            ))
         );
     }
+
+    #[test]
+    fn test_parse_errors() {
+        let parser = MsfParser::new(String::new());
+        let parse = |input: &[u8]| parser.parse_impl(std::io::BufReader::new(input));
+        assert!(matches!(parse(b""), Err(ParseError::Empty)));
+        assert!(matches!(
+            parse(b"version 9\n()"),
+            Err(ParseError::UnsupportedVersion(9))
+        ));
+        // locations count the version line
+        match parse(b"version 4\n(\n    game_title: 5,\n)") {
+            Err(ParseError::Syntax { location, .. }) => assert_eq!(location.line, 3),
+            other => panic!("expected a syntax error, got {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,86 @@
+use super::{TimeType, TimingMethod};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+/// A single recorded attempt of a run, whether it was finished or reset.
+pub struct Attempt {
+    id: u32,
+    started: u128,
+    ended: u128,
+    times: Vec<TimeType>,
+    #[serde(default)]
+    game_times: Vec<TimeType>,
+    pause_time: u128,
+}
+
+impl Attempt {
+    /// Create a new [`Attempt`].
+    ///
+    /// `started` and `ended` are wall-clock timestamps in milliseconds since the unix epoch.
+    /// `times` holds one entry per split, with [`TimeType::None`] for splits that were never
+    /// reached because the attempt was reset. `game_times` is the same, but measured in game time.
+    pub fn new(
+        id: u32,
+        started: u128,
+        ended: u128,
+        times: &[TimeType],
+        game_times: &[TimeType],
+        pause_time: u128,
+    ) -> Self {
+        Attempt {
+            id,
+            started,
+            ended,
+            times: times.to_owned(),
+            game_times: game_times.to_owned(),
+            pause_time,
+        }
+    }
+    /// Get the number identifying this attempt. Attempts are numbered from 1.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+    /// Get the time the attempt was started, in milliseconds since the unix epoch.
+    pub fn started(&self) -> u128 {
+        self.started
+    }
+    /// Get the time the attempt was finished or reset, in milliseconds since the unix epoch.
+    pub fn ended(&self) -> u128 {
+        self.ended
+    }
+    /// Get the time spent on each split in the attempt.
+    pub fn times(&self) -> &Vec<TimeType> {
+        &self.times
+    }
+    /// Get the time spent on each split in the attempt as measured by `method`.
+    pub fn times_of(&self, method: TimingMethod) -> &Vec<TimeType> {
+        match method {
+            TimingMethod::RealTime => &self.times,
+            TimingMethod::GameTime => &self.game_times,
+        }
+    }
+    /// Get the total amount of time the timer spent paused during the attempt.
+    pub fn pause_time(&self) -> u128 {
+        self.pause_time
+    }
+    /// Whether the attempt made it through the final split.
+    pub fn is_finished(&self) -> bool {
+        matches!(self.times.last(), Some(t) if !t.is_none())
+    }
+    /// Get the total time of the attempt, including skipped splits.
+    ///
+    /// Returns `None` if the attempt was not finished.
+    pub fn time(&self) -> TimeType {
+        self.time_of(TimingMethod::RealTime)
+    }
+    /// Get the total time of the attempt as measured by `method`, including skipped splits.
+    ///
+    /// Returns `None` if the attempt was not finished.
+    pub fn time_of(&self, method: TimingMethod) -> TimeType {
+        if self.is_finished() {
+            TimeType::Time(self.times_of(method).iter().map(|t| t.raw()).sum())
+        } else {
+            TimeType::None
+        }
+    }
+}
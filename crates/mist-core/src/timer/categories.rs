@@ -0,0 +1,73 @@
+use super::Run;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+/// Several [`Run`]s kept in the same split file, usually different categories of one game.
+pub struct Categories {
+    selected: usize,
+    runs: Vec<Run>,
+}
+
+impl Categories {
+    /// Create a new [`Categories`] from `runs`, with the run at `selected` loaded by default.
+    ///
+    /// There is always at least one run, so an empty `runs` holds a single empty [`Run`].
+    pub fn new(runs: &[Run], selected: usize) -> Self {
+        let runs = if runs.is_empty() {
+            vec![Run::empty()]
+        } else {
+            runs.to_owned()
+        };
+        Categories {
+            selected: selected.min(runs.len() - 1),
+            runs,
+        }
+    }
+    /// Get every run.
+    pub fn runs(&self) -> &Vec<Run> {
+        &self.runs
+    }
+    /// Get the number of runs.
+    pub fn len(&self) -> usize {
+        self.runs.len()
+    }
+    /// Whether there are no runs. This is always false.
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+    /// Get the index of the run that is loaded by default.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+    /// Get the run that is loaded by default.
+    pub fn selected_run(&self) -> &Run {
+        &self.runs[self.selected]
+    }
+    /// Take the run that is loaded by default, dropping the rest.
+    pub fn into_selected(mut self) -> Run {
+        self.runs.swap_remove(self.selected)
+    }
+    /// Set the run that is loaded by default. Does nothing if there is no run at `idx`.
+    pub fn set_selected(&mut self, idx: usize) {
+        if idx < self.runs.len() {
+            self.selected = idx;
+        }
+    }
+    /// Replace the run at `idx`.
+    pub fn set_run(&mut self, idx: usize, run: Run) {
+        self.runs[idx] = run;
+    }
+    /// Add a run to the end.
+    pub fn push_run(&mut self, run: Run) {
+        self.runs.push(run);
+    }
+}
+
+impl From<Run> for Categories {
+    fn from(run: Run) -> Self {
+        Categories {
+            selected: 0,
+            runs: vec![run],
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use super::MistInstant;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// A source of time for a [`RunState`](super::state::RunState).
+pub trait Clock {
+    /// Get the amount of time that has passed since the clock was started.
+    fn elapsed(&self) -> Duration;
+}
+
+impl Clock for MistInstant {
+    fn elapsed(&self) -> Duration {
+        MistInstant::elapsed(self)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+/// A [`Clock`] that only moves when it is told to.
+///
+/// Clones share the same time, so one can be given to a [`RunState`](super::state::RunState) and
+/// another kept to drive it, e.g. in tests or when replaying recorded timestamps.
+pub struct ManualClock {
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    /// Create a new [`ManualClock`] starting at zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Move the clock forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        *self.elapsed.lock().unwrap() += by;
+    }
+    /// Set the time that has passed since the clock was started.
+    pub fn set(&self, elapsed: Duration) {
+        *self.elapsed.lock().unwrap() = elapsed;
+    }
+}
+
+impl Clock for ManualClock {
+    fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap()
+    }
+}
@@ -1,3 +1,4 @@
+use super::{generate, Run, TimeType, TimingMethod};
 use serde::{Deserialize, Serialize};
 
 /// What a run is comparing to.
@@ -6,43 +7,97 @@ pub enum Comparison {
     Average,
     PersonalBest,
     Golds,
+    /// Median time of each split over the attempt history.
+    MedianSegments,
+    /// The most recent finished attempt.
+    LatestRun,
+    /// Best cumulative time reached at each split.
+    BestSplitTimes,
+    /// The pb time spread over the splits according to the attempt history.
+    BalancedPb,
+    /// The run's [`Target`](super::Target) time spread over the splits.
+    Target,
+    /// One of the run's [`CustomComparison`](super::CustomComparison)s, by index.
+    Custom(usize),
+    /// The personal best of the run's ghost, if one is loaded.
+    Ghost,
     None,
 }
 
 impl Comparison {
     /// Switch to the next comparison.
-    pub fn next(&mut self) {
-        match self {
-            Comparison::Average => {
-                *self = Comparison::PersonalBest;
-            }
-            Comparison::PersonalBest => {
-                *self = Comparison::Golds;
-            }
-            Comparison::Golds => {
-                *self = Comparison::None;
-            }
-            Comparison::None => {
-                *self = Comparison::Average;
-            }
-        }
+    ///
+    /// The target of `run` comes after the generated comparisons if it has one, then its custom
+    /// comparisons, followed by its ghost if it has one.
+    pub fn next(&mut self, run: &Run) {
+        let all = Self::all(run);
+        let idx = all.iter().position(|c| c == self).unwrap_or(0);
+        *self = all[(idx + 1) % all.len()];
     }
 
     /// Switch to the previous comparison.
-    pub fn prev(&mut self) {
+    ///
+    /// The target of `run` comes after the generated comparisons if it has one, then its custom
+    /// comparisons, followed by its ghost if it has one.
+    pub fn prev(&mut self, run: &Run) {
+        let all = Self::all(run);
+        let idx = all.iter().position(|c| c == self).unwrap_or(0);
+        *self = all[(idx + all.len() - 1) % all.len()];
+    }
+
+    /// Get the time this comparison expects to be spent on each split of `run`, as measured by `method`.
+    ///
+    /// Splits that the comparison has no time for are `TimeType::None`.
+    pub fn segments(&self, run: &Run, method: TimingMethod) -> Vec<TimeType> {
+        let len = run.splits().len();
         match self {
-            Comparison::Average => {
-                *self = Comparison::None;
-            }
-            Comparison::PersonalBest => {
-                *self = Comparison::Average;
-            }
-            Comparison::Golds => {
-                *self = Comparison::PersonalBest;
-            }
-            Comparison::None => {
-                *self = Comparison::Golds;
-            }
+            Comparison::PersonalBest => run.pb_times_of(method).to_owned(),
+            Comparison::Golds => run.gold_times_of(method).to_owned(),
+            Comparison::Average => generate::average_segments(run, method),
+            Comparison::MedianSegments => generate::median_segments(run, method),
+            Comparison::LatestRun => generate::latest_run(run, method),
+            Comparison::BestSplitTimes => generate::best_split_times(run, method),
+            Comparison::BalancedPb => generate::balanced_pb(run, method),
+            Comparison::Target => generate::target_segments(run, method),
+            Comparison::Custom(idx) => match run.comparisons().get(*idx) {
+                Some(c) => {
+                    let mut times = c.times_of(method).to_owned();
+                    times.resize_with(len, Default::default);
+                    times
+                }
+                None => vec![TimeType::None; len],
+            },
+            Comparison::Ghost => match run.ghost() {
+                Some(g) => {
+                    let mut times = g.times_of(method).to_owned();
+                    times.resize_with(len, Default::default);
+                    times
+                }
+                None => vec![TimeType::None; len],
+            },
+            Comparison::None => vec![TimeType::None; len],
+        }
+    }
+
+    // every comparison in the order they are cycled through
+    fn all(run: &Run) -> Vec<Comparison> {
+        let mut all = vec![
+            Comparison::Average,
+            Comparison::PersonalBest,
+            Comparison::Golds,
+            Comparison::MedianSegments,
+            Comparison::LatestRun,
+            Comparison::BestSplitTimes,
+            Comparison::BalancedPb,
+        ];
+        if run.target().is_some() {
+            all.push(Comparison::Target);
+        }
+        all.extend((0..run.comparisons().len()).map(Comparison::Custom));
+        if run.ghost().is_some() {
+            all.push(Comparison::Ghost);
         }
+        all.push(Comparison::None);
+        all
     }
 }
@@ -0,0 +1,52 @@
+use super::{Run, TimeType, TimingMethod};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+/// A named set of split times stored in a [`Run`](super::Run) to compare against, like a friend's splits or an old pb.
+pub struct CustomComparison {
+    name: String,
+    times: Vec<TimeType>,
+    #[serde(default)]
+    game_times: Vec<TimeType>,
+}
+
+impl CustomComparison {
+    /// Create a new [`CustomComparison`].
+    ///
+    /// `times` and `game_times` hold the segment time for each split, in real time and game time.
+    pub fn new<S>(name: S, times: &[TimeType], game_times: &[TimeType]) -> Self
+    where
+        S: ToString,
+    {
+        CustomComparison {
+            name: name.to_string(),
+            times: times.to_owned(),
+            game_times: game_times.to_owned(),
+        }
+    }
+    /// Create a [`CustomComparison`] from the personal best of another run, named after its category.
+    ///
+    /// Used to race against a ghost.
+    pub fn from_pb(run: &Run) -> Self {
+        Self::new(
+            run.category(),
+            run.pb_times(),
+            run.pb_times_of(TimingMethod::GameTime),
+        )
+    }
+    /// Get the name of the comparison.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Get the segment time of each split in the comparison.
+    pub fn times(&self) -> &Vec<TimeType> {
+        &self.times
+    }
+    /// Get the segment time of each split in the comparison as measured by `method`.
+    pub fn times_of(&self, method: TimingMethod) -> &Vec<TimeType> {
+        match method {
+            TimingMethod::RealTime => &self.times,
+            TimingMethod::GameTime => &self.game_times,
+        }
+    }
+}
@@ -1,11 +1,14 @@
 //! Snapshots of mist's state, for restoration.
-use super::{state::SplitStatus, Comparison, DiffType, Run, TimeType};
+use super::{
+    state::SplitStatus, Comparison, DiffType, Run, TimeType, TimingMethod, NANOS_PER_MILLI,
+};
+use crate::error::ParseError;
 use ron::{de::from_reader, ser::to_string};
 use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::path::Path;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 /// A snapshot of mist's state.
 ///
 /// This can be used for serialization to disk so that the user can stop and pick
@@ -14,17 +17,43 @@ use std::path::Path;
 ///
 /// All fields are meaningful either in [`RunState`](super::state::RunState) or in the renderer.
 pub struct StateDump {
+    /// Format of the dump. Dumps from before version 1 store times in milliseconds, and dumps
+    /// from before version 2 do not record which split file and category they came from.
+    #[serde(default)]
+    pub version: u32,
+    /// Path of the split file the run was loaded from. Empty if it has not been saved.
+    #[serde(default)]
+    pub file: String,
+    /// Index of the run's category in its split file.
+    #[serde(default)]
+    pub category: usize,
     pub run: Run,
     pub status: SplitStatus,
     pub comparison: Comparison,
+    #[serde(default)]
+    pub method: TimingMethod,
     pub run_times: Vec<TimeType>,
+    #[serde(default)]
+    pub game_run_times: Vec<TimeType>,
     pub run_diffs: Vec<DiffType>,
     pub run_golds: Vec<bool>,
+    #[serde(default)]
+    pub game_run_golds: Vec<bool>,
     pub sum_comp_times: Vec<u128>,
     pub before_pause: u128,
     pub before_pause_split: u128,
     pub time: u128,
+    #[serde(default)]
+    pub game_split: u128,
+    #[serde(default)]
+    pub game_time: u128,
+    #[serde(default)]
+    pub game_paused: bool,
     pub current_split: usize,
+    #[serde(default)]
+    pub attempt_start: u128,
+    #[serde(default)]
+    pub pause_time: u128,
     pub needs_save: bool,
     pub top_index: usize,
     pub bottom_index: usize,
@@ -35,10 +64,19 @@ impl StateDump {
     /// Open a `StateDump` from a file.
     ///
     /// Reads the file specified by `filename` and attempts to parse a `StateDump`
-    /// from the contents. Returns `Err` on an fs error or if the file is not
-    /// parseable.
-    pub fn open<P: AsRef<Path>>(filename: P) -> Result<Self, String> {
-        from_reader(File::open(filename).map_err(|e| e.to_string())?).map_err(|e| e.to_string())
+    /// from the contents. Returns [`ParseError::Io`] on an fs error or
+    /// [`ParseError::Syntax`] if the file is not parseable.
+    pub fn open<P: AsRef<Path>>(filename: P) -> Result<Self, ParseError> {
+        let mut dump: Self = from_reader(File::open(filename)?).map_err(ParseError::syntax)?;
+        if dump.version < 1 {
+            dump.ms_to_ns();
+        }
+        Ok(dump)
+    }
+    /// Set the split file and category that the run was loaded from.
+    pub fn set_source(&mut self, file: &str, category: usize) {
+        self.file = file.to_owned();
+        self.category = category;
     }
     /// Set the information required from the renderer.
     pub fn set_render_info(&mut self, top_index: usize, bottom_index: usize, time_str: String) {
@@ -46,11 +84,35 @@ impl StateDump {
         self.bottom_index = bottom_index;
         self.time_str = time_str;
     }
+    fn ms_to_ns(&mut self) {
+        self.run.ms_to_ns();
+        for t in self
+            .run_times
+            .iter_mut()
+            .chain(self.game_run_times.iter_mut())
+        {
+            *t = t.ms_to_ns();
+        }
+        for d in &mut self.run_diffs {
+            *d = d.ms_to_ns();
+        }
+        for t in self.sum_comp_times.iter_mut().chain([
+            &mut self.before_pause,
+            &mut self.before_pause_split,
+            &mut self.time,
+            &mut self.game_split,
+            &mut self.game_time,
+            &mut self.pause_time,
+        ]) {
+            *t *= NANOS_PER_MILLI;
+        }
+        self.version = 1;
+    }
     /// Serialize the `StateDump` to a file.
     ///
-    /// Returns `Err` on fs error or if the dump could not be serialized.
-    pub fn write<P: AsRef<Path>>(&self, filename: P) -> Result<(), String> {
-        std::fs::write(filename, to_string(self).map_err(|e| e.to_string())?)
-            .map_err(|e| e.to_string())
+    /// Returns [`ParseError::Io`] on fs error or [`ParseError::Serialize`] if the dump could not be serialized.
+    pub fn write<P: AsRef<Path>>(&self, filename: P) -> Result<(), ParseError> {
+        std::fs::write(filename, to_string(self).map_err(ParseError::Serialize)?)?;
+        Ok(())
     }
 }
@@ -1,16 +1,23 @@
 //! Functions used for formatting of times.
+//!
+//! All times passed in are in nanoseconds, and are only cut down to milliseconds here.
+use super::NANOS_PER_MILLI;
 use std::ops::AddAssign;
 
-/// Convert milliseconds into a readable time in the form HH:MM:SS.mmm.
+// nanoseconds in a tenth of a second
+const NANOS_PER_TENTH: u128 = NANOS_PER_MILLI * 100;
+
+/// Convert nanoseconds into a readable time in the form HH:MM:SS.mmm.
 ///
 /// Optionally rounds to a valid number of milliseconds on which a frame
 /// can occur, at any framerate.
 ///
 /// # Arguments
 ///
-/// * `ms` - the value to convert to string.
+/// * `ns` - the value to convert to string.
 /// * `round` - `Some(value)` to round to `value` frames/sec. `None` for no rounding.
-pub fn ms_to_readable(mut ms: u128, round: Option<u128>) -> String {
+pub fn ns_to_readable(ns: u128, round: Option<u128>) -> String {
+    let mut ms = ns / NANOS_PER_MILLI;
     if let Some(r) = round {
         if r != 0 {
             ms = round_ms(r, ms);
@@ -39,21 +46,51 @@ pub fn ms_to_readable(mut ms: u128, round: Option<u128>) -> String {
     return format!("0.{:03}", ms);
 }
 
+/// Read a time written like [ns_to_readable] writes them, e.g. `1:02:03.456`, `2:03` or `3.5`, as nanoseconds.
+///
+/// Returns `None` if `text` is not a time.
+pub fn readable_to_ns(text: &str) -> Option<u128> {
+    let text = text.trim();
+    let (whole, frac) = text.split_once('.').unwrap_or((text, ""));
+    if frac.len() > 3 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    // pad so that e.g. `.5` is 500 milliseconds rather than 5
+    let ms = format!("{:0<3}", frac).parse::<u128>().ok()?;
+    let parts = whole.split(':').collect::<Vec<_>>();
+    if parts.len() > 3 {
+        return None;
+    }
+    let mut secs = 0;
+    for (i, part) in parts.iter().enumerate() {
+        if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let num = part.parse::<u128>().ok()?;
+        // only the first part is allowed to go past 59
+        if i != 0 && num >= 60 {
+            return None;
+        }
+        secs = secs * 60 + num;
+    }
+    Some((secs * 1000 + ms) * NANOS_PER_MILLI)
+}
+
 /// Create the readable time for a time differences.
 ///
 /// Prefixes with `+` for lost time and `-` for gained time.
 ///
-/// Passing a negative value of `ms` specifies gained time and returns a `-` prefixed string.
+/// Passing a negative value of `ns` specifies gained time and returns a `-` prefixed string.
 ///
 /// Truncates decimals at the tenths place.
-pub fn diff_text(mut ms: i128) -> String {
-    let pre: char = if ms < 0 {
-        ms *= -1;
+pub fn ns_diff_text(mut ns: i128) -> String {
+    let pre: char = if ns < 0 {
+        ns *= -1;
         '-'
     } else {
         '+'
     };
-    let mut tenths = ms / 100;
+    let mut tenths = ns / NANOS_PER_TENTH as i128;
     let mut full_s = tenths / 10;
     tenths -= full_s * 10;
     if full_s >= 60 {
@@ -73,9 +110,9 @@ pub fn diff_text(mut ms: i128) -> String {
 
 /// Creates the text for times of splits.
 ///
-/// Essentially the same as [ms_to_readable] but truncates at tenths place.
-pub fn split_time_text(ms: u128) -> String {
-    let mut tenths = ms / 100;
+/// Essentially the same as [ns_to_readable] but truncates at tenths place.
+pub fn ns_split_time_text(ns: u128) -> String {
+    let mut tenths = ns / NANOS_PER_TENTH;
     let mut full_s: u128;
     if tenths > 10 {
         full_s = tenths / 10;
@@ -103,10 +140,10 @@ pub fn split_time_text(ms: u128) -> String {
 /// Returns a Vec with the sums of every element up to that point in it.
 ///
 /// For example, input of [6, 7, 8] returns [6, 13, 21].
-pub fn split_time_sum<T: AddAssign + Default + Copy>(ms_vec: &[T]) -> Vec<T> {
+pub fn split_time_sum<T: AddAssign + Default + Copy>(times: &[T]) -> Vec<T> {
     let mut total = Default::default();
     let mut vec = vec![];
-    for &num in ms_vec {
+    for &num in times {
         total += num;
         vec.push(total);
     }
@@ -150,7 +187,25 @@ mod tests {
     #[test]
     // 3,611,111 ms should be 1 hour, 1 minute, 1 second, 111 ms
     fn test_readable() {
-        assert_eq!(ms_to_readable(3_661_111, None), "1:01:01.111");
+        assert_eq!(ns_to_readable(3_661_111_000_000, None), "1:01:01.111");
+        // anything under a millisecond is cut off
+        assert_eq!(ns_to_readable(3_661_111_999_999, None), "1:01:01.111");
+    }
+    #[test]
+    fn test_from_readable() {
+        assert_eq!(readable_to_ns("1:01:01.111"), Some(3_661_111_000_000));
+        assert_eq!(readable_to_ns("1:30:00"), Some(5_400_000_000_000));
+        assert_eq!(readable_to_ns("2:03"), Some(123_000_000_000));
+        assert_eq!(readable_to_ns("3.5"), Some(3_500_000_000));
+        assert_eq!(
+            readable_to_ns(&ns_to_readable(3_661_111_000_000, None)),
+            Some(3_661_111_000_000)
+        );
+        assert_eq!(readable_to_ns("1:60"), None);
+        assert_eq!(readable_to_ns("1.2345"), None);
+        assert_eq!(readable_to_ns("1::00"), None);
+        assert_eq!(readable_to_ns("abc"), None);
+        assert_eq!(readable_to_ns(""), None);
     }
     #[test]
     fn test_rounding_30() {
@@ -176,11 +231,11 @@ mod tests {
     }
     #[test]
     fn test_diff() {
-        assert_eq!(diff_text(3_661_111), "+1:01:01.1");
-        assert_eq!(diff_text(-3_661_111), "-1:01:01.1");
+        assert_eq!(ns_diff_text(3_661_111_000_000), "+1:01:01.1");
+        assert_eq!(ns_diff_text(-3_661_111_000_000), "-1:01:01.1");
     }
     #[test]
     fn test_split() {
-        assert_eq!(split_time_text(3_661_111), "1:01:01.1");
+        assert_eq!(ns_split_time_text(3_661_111_000_000), "1:01:01.1");
     }
 }
@@ -0,0 +1,229 @@
+//! Generate comparisons from a [`Run`]'s times and attempt history.
+//!
+//! Every generator returns the time expected to be spent on each split (not cumulative), with
+//! [`TimeType::None`] for splits it has no data for, so that the results can be summed just like
+//! the pb or gold times.
+use super::{Run, TargetBasis, TimeType, TimingMethod};
+
+/// Average time spent on each split, from the run's sum times.
+pub fn average_segments(run: &Run, method: TimingMethod) -> Vec<TimeType> {
+    run.sum_times_of(method)
+        .iter()
+        .map(|&(n, t)| if n != 0 { TimeType::Time(t / n) } else { t })
+        .collect()
+}
+
+/// Median time spent on each split over every recorded attempt that completed it.
+pub fn median_segments(run: &Run, method: TimingMethod) -> Vec<TimeType> {
+    segment_history(run, method)
+        .iter()
+        .map(|h| percentile(h, 0.5).map_or(TimeType::None, |t| TimeType::Time(t.round() as u128)))
+        .collect()
+}
+
+/// Split times of the most recent finished attempt.
+pub fn latest_run(run: &Run, method: TimingMethod) -> Vec<TimeType> {
+    match run.attempts().iter().rev().find(|a| a.is_finished()) {
+        Some(a) => {
+            let mut times = a.times_of(method).to_owned();
+            times.resize_with(run.splits().len(), Default::default);
+            times
+        }
+        None => vec![TimeType::None; run.splits().len()],
+    }
+}
+
+/// Segments that add up to the best cumulative time reached at each split by any attempt.
+///
+/// Unlike the sum of golds, every cumulative time here was actually reached in a single attempt.
+pub fn best_split_times(run: &Run, method: TimingMethod) -> Vec<TimeType> {
+    let len = run.splits().len();
+    let mut best: Vec<Option<u128>> = vec![None; len];
+    for attempt in run.attempts() {
+        let mut total = 0;
+        for (i, t) in attempt.times_of(method).iter().take(len).enumerate() {
+            if t.is_none() {
+                break;
+            }
+            total += t.raw();
+            if !matches!(best[i], Some(b) if b <= total) {
+                best[i] = Some(total);
+            }
+        }
+    }
+    let mut last = 0;
+    best.iter()
+        .map(|b| match *b {
+            // a split can only be reached after the one before it, so this never goes backwards
+            Some(t) => {
+                let seg = t - last;
+                last = t;
+                TimeType::Time(seg)
+            }
+            None => TimeType::None,
+        })
+        .collect()
+}
+
+/// Segments that add up to the pb, spread over the splits according to their attempt history.
+///
+/// Every split is given the same percentile of its own history, chosen so that the total is as
+/// close to the pb as possible. Falls back to the pb times if the pb is not set or any split has
+/// no history.
+pub fn balanced_pb(run: &Run, method: TimingMethod) -> Vec<TimeType> {
+    let pb = run.pb_of(method);
+    let history = segment_history(run, method);
+    if !pb.is_time() || history.is_empty() || history.iter().any(|h| h.is_empty()) {
+        return run.pb_times_of(method).to_owned();
+    }
+    let target = pb.val() as f64;
+    let total = |p: f64| -> f64 { history.iter().filter_map(|h| percentile(h, p)).sum() };
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..64 {
+        let mid = (low + high) / 2.0;
+        if total(mid) < target {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    history
+        .iter()
+        .map(|h| TimeType::Time(percentile(h, high).unwrap().round() as u128))
+        .collect()
+}
+
+/// Segments that add up to the run's [`Target`](super::Target), spread over the splits in
+/// proportion to either the golds or the pb depending on its [`TargetBasis`].
+///
+/// Every split is `TimeType::None` if the run has no target or any split has no time to spread by.
+pub fn target_segments(run: &Run, method: TimingMethod) -> Vec<TimeType> {
+    let len = run.splits().len();
+    let target = match run.target() {
+        Some(t) => t,
+        None => return vec![TimeType::None; len],
+    };
+    let basis = match target.basis() {
+        TargetBasis::Golds => run.gold_times_of(method),
+        TargetBasis::PersonalBest => run.pb_times_of(method),
+    };
+    let total: u128 = basis.iter().map(|t| t.val()).sum();
+    if basis.len() != len || basis.iter().any(|t| !t.is_time()) || total == 0 {
+        return vec![TimeType::None; len];
+    }
+    // round the cumulative times rather than each segment so they add up to exactly the target
+    let mut sum = 0;
+    let mut last = 0;
+    basis
+        .iter()
+        .map(|t| {
+            sum += t.val();
+            let cumulative = (target.time() as f64 * sum as f64 / total as f64).round() as u128;
+            let seg = cumulative - last;
+            last = cumulative;
+            TimeType::Time(seg)
+        })
+        .collect()
+}
+
+// every completed, unskipped time for each split, sorted
+pub(super) fn segment_history(run: &Run, method: TimingMethod) -> Vec<Vec<f64>> {
+    let len = run.splits().len();
+    let mut history = vec![vec![]; len];
+    for attempt in run.attempts() {
+        for (i, t) in attempt.times_of(method).iter().take(len).enumerate() {
+            if let TimeType::Time(t) = t {
+                history[i].push(*t as f64);
+            }
+        }
+    }
+    for h in &mut history {
+        h.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    }
+    history
+}
+
+// linearly interpolated percentile of sorted values, `p` in [0, 1]
+pub(super) fn percentile(sorted: &[f64], p: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let pos = p * (sorted.len() - 1) as f64;
+    let lower = pos.floor() as usize;
+    let upper = pos.ceil() as usize;
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (pos - lower as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::{Attempt, Target, TimeType::*, TimingMethod::RealTime};
+
+    fn test_run() -> Run {
+        let mut run = Run::new(
+            "test",
+            "test",
+            None,
+            Time(300),
+            &["a".into(), "b".into()],
+            &[Time(100), Time(200)],
+            &[Time(90), Time(150)],
+            &[(3, Time(360)), (2, Time(350))],
+        );
+        run.set_attempts(&[
+            Attempt::new(1, 0, 0, &[Time(100), Time(200)], &[], 0),
+            Attempt::new(2, 0, 0, &[Time(90), Time(250)], &[], 0),
+            Attempt::new(3, 0, 0, &[Time(170), None], &[], 0),
+        ]);
+        run
+    }
+
+    #[test]
+    fn test_average() {
+        assert_eq!(
+            average_segments(&test_run(), RealTime),
+            vec![Time(120), Time(175)]
+        );
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(
+            median_segments(&test_run(), RealTime),
+            vec![Time(100), Time(225)]
+        );
+    }
+
+    #[test]
+    fn test_latest() {
+        assert_eq!(latest_run(&test_run(), RealTime), vec![Time(90), Time(250)]);
+    }
+
+    #[test]
+    fn test_best_splits() {
+        assert_eq!(
+            best_split_times(&test_run(), RealTime),
+            vec![Time(90), Time(210)]
+        );
+    }
+
+    #[test]
+    fn test_balanced_pb() {
+        let balanced = balanced_pb(&test_run(), RealTime);
+        let total: u128 = balanced.iter().map(|t| t.val()).sum();
+        assert!((299..=301).contains(&total));
+    }
+
+    #[test]
+    fn test_target() {
+        let mut run = test_run();
+        assert_eq!(target_segments(&run, RealTime), vec![None, None]);
+        run.set_target(Some(Target::new(480, TargetBasis::Golds)));
+        assert_eq!(target_segments(&run, RealTime), vec![Time(180), Time(300)]);
+        run.set_target(Some(Target::new(100, TargetBasis::PersonalBest)));
+        assert_eq!(target_segments(&run, RealTime), vec![Time(33), Time(67)]);
+        run.set_gold_times(&[Time(90), None]);
+        run.set_target(Some(Target::new(480, TargetBasis::Golds)));
+        assert_eq!(target_segments(&run, RealTime), vec![None, None]);
+    }
+}
@@ -0,0 +1,70 @@
+use super::state::{SplitStatus, StateChangeRequest};
+use super::{Comparison, Run, TimingMethod};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// A thread-safe way to observe and control a [`RunState`](super::state::RunState).
+///
+/// Get one with [`RunState::handle`](super::state::RunState::handle). Handles can be cloned and sent
+/// to other threads (e.g. for network overlays, autosaving or global hotkeys). Every call to
+/// [`update`](super::state::RunState::update) publishes a new [`RunSnapshot`] to the handle, and
+/// handles requests made through it along with the ones passed to it.
+#[derive(Clone, Debug)]
+pub struct RunHandle {
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    snapshot: RwLock<RunSnapshot>,
+    requests: Mutex<Vec<StateChangeRequest>>,
+}
+
+/// The state of a [`RunState`](super::state::RunState) as of its last update.
+#[derive(Clone, Debug)]
+pub struct RunSnapshot {
+    /// The run being timed.
+    pub run: Arc<Run>,
+    /// Amount of time that has passed in the current split.
+    pub split_time: u128,
+    /// Amount of time that has passed in the run as a whole.
+    pub time: u128,
+    /// Amount of game time that has passed in the current split.
+    pub game_split_time: u128,
+    /// Amount of game time that has passed in the run as a whole.
+    pub game_time: u128,
+    /// Index of the current split.
+    pub current_split: usize,
+    /// Whether the timer is running.
+    pub running: bool,
+    /// Whether run is ahead, behind, etc.
+    pub status: SplitStatus,
+    /// What the run is being compared to.
+    pub comparison: Comparison,
+    /// The timing method used for comparisons.
+    pub method: TimingMethod,
+}
+
+impl RunHandle {
+    pub(super) fn new(snapshot: RunSnapshot) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                snapshot: RwLock::new(snapshot),
+                requests: Mutex::new(vec![]),
+            }),
+        }
+    }
+    /// Get the most recently published state.
+    pub fn snapshot(&self) -> RunSnapshot {
+        self.shared.snapshot.read().unwrap().clone()
+    }
+    /// Ask the [`RunState`](super::state::RunState) to change its state on its next update.
+    pub fn request(&self, rq: StateChangeRequest) {
+        self.shared.requests.lock().unwrap().push(rq);
+    }
+    pub(super) fn take_requests(&self) -> Vec<StateChangeRequest> {
+        std::mem::take(&mut *self.shared.requests.lock().unwrap())
+    }
+    pub(super) fn publish(&self, snapshot: RunSnapshot) {
+        *self.shared.snapshot.write().unwrap() = snapshot;
+    }
+}
@@ -0,0 +1,114 @@
+use crate::error::ParseError;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq)]
+/// An ordered list of split files that are run back to back, e.g. for marathons and relays.
+///
+/// Only keeps track of which split file is being run. Timing the marathon as a whole is up to
+/// the application.
+pub struct Marathon {
+    files: Vec<PathBuf>,
+    current: usize,
+}
+
+impl Marathon {
+    /// Create a new [`Marathon`] that starts at the first of `files`.
+    pub fn new(files: &[PathBuf]) -> Self {
+        Marathon {
+            files: files.to_owned(),
+            current: 0,
+        }
+    }
+    /// Open a playlist file.
+    ///
+    /// The playlist has the path of one split file on each line, in the order they are run.
+    /// Relative paths are relative to the playlist itself. Blank lines and lines starting with `#`
+    /// are skipped. Returns [`ParseError::Empty`] if there are no split files in the playlist.
+    pub fn open<P: AsRef<Path>>(filename: P) -> Result<Self, ParseError> {
+        let filename = filename.as_ref();
+        let dir = filename.parent().unwrap_or_else(|| Path::new(""));
+        Self::parse(BufReader::new(File::open(filename)?), dir)
+    }
+    fn parse<R: BufRead>(reader: R, dir: &Path) -> Result<Self, ParseError> {
+        let mut files = vec![];
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            files.push(dir.join(line));
+        }
+        if files.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        Ok(Self::new(&files))
+    }
+    /// Get every split file in the marathon, in order.
+    pub fn files(&self) -> &Vec<PathBuf> {
+        &self.files
+    }
+    /// Get the number of split files.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+    /// Whether there are no split files.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+    /// Get the index of the split file being run.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+    /// Get the split file being run.
+    pub fn current_file(&self) -> Option<&Path> {
+        self.files.get(self.current).map(|f| f.as_path())
+    }
+    /// Whether the split file being run is the last one.
+    pub fn is_last(&self) -> bool {
+        self.current + 1 >= self.files.len()
+    }
+    /// Move on to the next split file after finishing a run.
+    ///
+    /// Returns the next split file, or [`None`] if that was the last one.
+    pub fn finish_run(&mut self) -> Option<&Path> {
+        if self.current < self.files.len() {
+            self.current += 1;
+        }
+        self.current_file()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let playlist = "# relay\na.msf\n\n  /splits/b.msf \n";
+        let marathon = Marathon::parse(playlist.as_bytes(), Path::new("dir")).unwrap();
+        assert_eq!(
+            marathon.files(),
+            &vec![PathBuf::from("dir/a.msf"), PathBuf::from("/splits/b.msf")]
+        );
+        assert!(matches!(
+            Marathon::parse("# nothing\n".as_bytes(), Path::new("")),
+            Err(ParseError::Empty)
+        ));
+    }
+
+    #[test]
+    fn test_finish_run() {
+        let mut marathon = Marathon::new(&["a.msf".into(), "b.msf".into()]);
+        assert_eq!(marathon.current_file(), Some(Path::new("a.msf")));
+        assert!(!marathon.is_last());
+        assert_eq!(marathon.finish_run(), Some(Path::new("b.msf")));
+        assert_eq!(marathon.current(), 1);
+        assert!(marathon.is_last());
+        assert_eq!(marathon.finish_run(), None);
+        assert_eq!(marathon.finish_run(), None);
+        assert_eq!(marathon.current(), 2);
+    }
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+/// Information identifying a [`Run`](super::Run) on speedrun.com, as kept by LiveSplit.
+pub struct Metadata {
+    run_id: String,
+    platform: String,
+    uses_emulator: bool,
+    region: String,
+    variables: Vec<(String, String)>,
+}
+
+impl Metadata {
+    /// Create a new [`Metadata`].
+    ///
+    /// `variables` holds the name and value of each speedrun.com variable, e.g. `("Difficulty", "Hard")`.
+    pub fn new<S>(
+        run_id: S,
+        platform: S,
+        uses_emulator: bool,
+        region: S,
+        variables: &[(String, String)],
+    ) -> Self
+    where
+        S: ToString,
+    {
+        Metadata {
+            run_id: run_id.to_string(),
+            platform: platform.to_string(),
+            uses_emulator,
+            region: region.to_string(),
+            variables: variables.to_owned(),
+        }
+    }
+    /// Get the speedrun.com id of the run. Empty if it has not been submitted.
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+    /// Get the platform the run is played on.
+    pub fn platform(&self) -> &str {
+        &self.platform
+    }
+    /// Whether the run is played on an emulator.
+    pub fn uses_emulator(&self) -> bool {
+        self.uses_emulator
+    }
+    /// Get the region of the game the run is played on.
+    pub fn region(&self) -> &str {
+        &self.region
+    }
+    /// Get the name and value of each speedrun.com variable.
+    pub fn variables(&self) -> &Vec<(String, String)> {
+        &self.variables
+    }
+    /// Whether none of the fields are set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
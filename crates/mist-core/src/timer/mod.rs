@@ -1,14 +1,38 @@
 //! Everything related to timing in mist
+mod attempt;
+mod categories;
+#[cfg(feature = "state")]
+mod clock;
 #[cfg(feature = "state")]
 mod comparison;
+mod custom_comparison;
 pub mod format;
+pub mod generate;
+#[cfg(feature = "state")]
+mod handle;
 mod instant;
+mod marathon;
+mod metadata;
+mod practice_segment;
 mod run;
+mod segment_group;
 #[cfg(feature = "state")]
 pub mod state;
+pub mod stats;
+mod target;
 mod time_type;
 #[cfg(feature = "state")]
+pub use clock::{Clock, ManualClock};
+#[cfg(feature = "state")]
 pub use comparison::Comparison;
-pub use time_type::{DiffType, TimeType};
-pub use {instant::MistInstant, run::Run};
+#[cfg(feature = "state")]
+pub use handle::{RunHandle, RunSnapshot};
+pub use marathon::Marathon;
+pub use metadata::Metadata;
+pub use target::{Target, TargetBasis};
+pub use time_type::{DiffType, TimeType, TimingMethod, NANOS_PER_MILLI};
+pub use {
+    attempt::Attempt, categories::Categories, custom_comparison::CustomComparison,
+    instant::MistInstant, practice_segment::PracticeSegment, run::Run, segment_group::SegmentGroup,
+};
 pub mod dump;
@@ -0,0 +1,44 @@
+use super::TimingMethod;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+/// A single segment timed while practicing, outside of a full attempt.
+pub struct PracticeSegment {
+    split: usize,
+    ended: u128,
+    time: u128,
+    game_time: u128,
+}
+
+impl PracticeSegment {
+    /// Create a new [`PracticeSegment`] for the split at index `split`.
+    ///
+    /// `ended` is a wall-clock timestamp in milliseconds since the unix epoch.
+    pub fn new(split: usize, ended: u128, time: u128, game_time: u128) -> Self {
+        PracticeSegment {
+            split,
+            ended,
+            time,
+            game_time,
+        }
+    }
+    /// Get the index of the split that was practiced.
+    pub fn split(&self) -> usize {
+        self.split
+    }
+    /// Get the time the segment was finished, in milliseconds since the unix epoch.
+    pub fn ended(&self) -> u128 {
+        self.ended
+    }
+    /// Get the time spent on the segment.
+    pub fn time(&self) -> u128 {
+        self.time
+    }
+    /// Get the time spent on the segment as measured by `method`.
+    pub fn time_of(&self, method: TimingMethod) -> u128 {
+        match method {
+            TimingMethod::RealTime => self.time,
+            TimingMethod::GameTime => self.game_time,
+        }
+    }
+}
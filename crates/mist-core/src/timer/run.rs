@@ -1,4 +1,7 @@
-use super::TimeType;
+use super::{
+    Attempt, CustomComparison, Metadata, PracticeSegment, SegmentGroup, Target, TimeType,
+    TimingMethod, NANOS_PER_MILLI,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -12,6 +15,33 @@ pub struct Run {
     pb_times: Vec<TimeType>,
     gold_times: Vec<TimeType>,
     sum_times: Vec<(u128, TimeType)>,
+    #[serde(default)]
+    game_pb: TimeType,
+    #[serde(default)]
+    game_pb_times: Vec<TimeType>,
+    #[serde(default)]
+    game_gold_times: Vec<TimeType>,
+    #[serde(default)]
+    game_sum_times: Vec<(u128, TimeType)>,
+    attempts: Vec<Attempt>,
+    #[serde(default)]
+    attempt_count: u32,
+    #[serde(default)]
+    comparisons: Vec<CustomComparison>,
+    #[serde(default)]
+    groups: Vec<SegmentGroup>,
+    #[serde(default)]
+    notes: Vec<String>,
+    #[serde(default)]
+    practice: Vec<PracticeSegment>,
+    #[serde(default)]
+    target: Option<Target>,
+    #[serde(default)]
+    countdown: Option<u128>,
+    #[serde(default)]
+    metadata: Metadata,
+    #[serde(skip)]
+    ghost: Option<CustomComparison>,
 }
 
 impl Run {
@@ -26,6 +56,20 @@ impl Run {
             pb_times: vec![],
             gold_times: vec![],
             sum_times: vec![],
+            game_pb: TimeType::None,
+            game_pb_times: vec![],
+            game_gold_times: vec![],
+            game_sum_times: vec![],
+            attempts: vec![],
+            attempt_count: 0,
+            comparisons: vec![],
+            groups: vec![],
+            notes: vec![],
+            practice: vec![],
+            target: None,
+            countdown: None,
+            metadata: Metadata::default(),
+            ghost: None,
         }
     }
     /// Create a new [`Run`].
@@ -52,6 +96,20 @@ impl Run {
             pb_times: pb_times.to_owned(),
             gold_times: gold_times.to_owned(),
             sum_times: sum_times.to_owned(),
+            game_pb: TimeType::None,
+            game_pb_times: vec![TimeType::None; splits.len()],
+            game_gold_times: vec![TimeType::None; splits.len()],
+            game_sum_times: vec![(0, TimeType::None); splits.len()],
+            attempts: vec![],
+            attempt_count: 0,
+            comparisons: vec![],
+            groups: vec![],
+            notes: vec![],
+            practice: vec![],
+            target: None,
+            countdown: None,
+            metadata: Metadata::default(),
+            ghost: None,
         }
     }
     /// Get the game title.
@@ -97,6 +155,109 @@ impl Run {
     pub fn sum_times(&self) -> &Vec<(u128, TimeType)> {
         &self.sum_times
     }
+    /// Get the pb of the run as measured by `method`.
+    pub fn pb_of(&self, method: TimingMethod) -> TimeType {
+        match method {
+            TimingMethod::RealTime => self.pb,
+            TimingMethod::GameTime => self.game_pb,
+        }
+    }
+    /// Returns the times set on each split on the last personal best as measured by `method`.
+    pub fn pb_times_of(&self, method: TimingMethod) -> &Vec<TimeType> {
+        match method {
+            TimingMethod::RealTime => &self.pb_times,
+            TimingMethod::GameTime => &self.game_pb_times,
+        }
+    }
+    /// Returns the best time achieved on each split as measured by `method`.
+    pub fn gold_times_of(&self, method: TimingMethod) -> &Vec<TimeType> {
+        match method {
+            TimingMethod::RealTime => &self.gold_times,
+            TimingMethod::GameTime => &self.game_gold_times,
+        }
+    }
+    /// Returns the attempt count and total time of each split as measured by `method`.
+    pub fn sum_times_of(&self, method: TimingMethod) -> &Vec<(u128, TimeType)> {
+        match method {
+            TimingMethod::RealTime => &self.sum_times,
+            TimingMethod::GameTime => &self.game_sum_times,
+        }
+    }
+    /// Returns every recorded attempt of the run, oldest first.
+    pub fn attempts(&self) -> &Vec<Attempt> {
+        &self.attempts
+    }
+    /// Get the number of attempts that have been started, including ones that are no longer in the
+    /// attempt history.
+    pub fn attempt_count(&self) -> u32 {
+        self.attempts
+            .last()
+            .map_or(0, |a| a.id())
+            .max(self.attempt_count)
+    }
+    /// Get the id that the next recorded attempt should use.
+    pub fn next_attempt_id(&self) -> u32 {
+        self.attempt_count() + 1
+    }
+    /// Returns every segment timed in practice mode, oldest first.
+    pub fn practice(&self) -> &Vec<PracticeSegment> {
+        &self.practice
+    }
+    /// Returns the user-defined comparisons of the run.
+    pub fn comparisons(&self) -> &Vec<CustomComparison> {
+        &self.comparisons
+    }
+    /// Get the ghost run being raced against, if one is loaded.
+    ///
+    /// The ghost is not saved to the split file.
+    pub fn ghost(&self) -> Option<&CustomComparison> {
+        self.ghost.as_ref()
+    }
+    /// Get the goal time for the run, if it has one.
+    pub fn target(&self) -> Option<Target> {
+        self.target
+    }
+    /// Get the time limit the timer counts down from, if the run has one.
+    pub fn countdown(&self) -> Option<u128> {
+        self.countdown
+    }
+    /// Get the information identifying the run on speedrun.com.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+    /// Returns the groups that splits are organized into, in order.
+    ///
+    /// Splits that are not part of any group are not listed.
+    pub fn groups(&self) -> &Vec<SegmentGroup> {
+        &self.groups
+    }
+    /// Returns the notes for each split. Splits without notes have an empty string.
+    pub fn notes(&self) -> &Vec<String> {
+        &self.notes
+    }
+    /// Get the notes for the split at `idx`, if it has any.
+    pub fn split_notes(&self, idx: usize) -> Option<&str> {
+        self.notes
+            .get(idx)
+            .map(|n| n.as_str())
+            .filter(|n| !n.is_empty())
+    }
+    /// Get the index of the group containing the split at `idx`, if there is one.
+    pub fn group_of(&self, idx: usize) -> Option<usize> {
+        self.groups.iter().position(|g| g.contains(idx))
+    }
+    /// Get the time of the whole group at `idx` on the current pb as measured by `method`.
+    ///
+    /// Returns `None` if any split in the group has no pb time.
+    pub fn group_pb_of(&self, idx: usize, method: TimingMethod) -> TimeType {
+        let group = &self.groups[idx];
+        let times = &self.pb_times_of(method)[group.start()..=group.end()];
+        if times.iter().any(|t| t.is_none()) {
+            TimeType::None
+        } else {
+            TimeType::Time(times.iter().map(|t| t.raw()).sum())
+        }
+    }
     /// Sets the game title.
     pub fn set_game_title<S>(&mut self, new: S)
     where
@@ -144,4 +305,145 @@ impl Run {
     pub fn set_sum_time(&mut self, idx: usize, new: (u128, TimeType)) {
         self.sum_times[idx] = new
     }
+    /// Set the pb of the run as measured by `method`.
+    pub fn set_pb_of(&mut self, method: TimingMethod, new: TimeType) {
+        match method {
+            TimingMethod::RealTime => self.pb = new,
+            TimingMethod::GameTime => self.game_pb = new,
+        }
+    }
+    /// Set the times for each split on the current pb as measured by `method`.
+    pub fn set_pb_times_of(&mut self, method: TimingMethod, new: &[TimeType]) {
+        match method {
+            TimingMethod::RealTime => self.pb_times = new.to_owned(),
+            TimingMethod::GameTime => self.game_pb_times = new.to_owned(),
+        }
+    }
+    /// Set the best time for each split as measured by `method`.
+    pub fn set_gold_times_of(&mut self, method: TimingMethod, new: &[TimeType]) {
+        match method {
+            TimingMethod::RealTime => self.gold_times = new.to_owned(),
+            TimingMethod::GameTime => self.game_gold_times = new.to_owned(),
+        }
+    }
+    /// Set a single gold time as measured by `method`, specified by `idx`.
+    pub fn set_gold_time_of(&mut self, method: TimingMethod, idx: usize, new: TimeType) {
+        match method {
+            TimingMethod::RealTime => self.gold_times[idx] = new,
+            TimingMethod::GameTime => self.game_gold_times[idx] = new,
+        }
+    }
+    /// Set the attempt count and total time for all splits as measured by `method`.
+    pub fn set_sum_times_of(&mut self, method: TimingMethod, new: &[(u128, TimeType)]) {
+        match method {
+            TimingMethod::RealTime => self.sum_times = new.to_owned(),
+            TimingMethod::GameTime => self.game_sum_times = new.to_owned(),
+        }
+    }
+    /// Set the attempt count and total for one split as measured by `method`, specified by `idx`.
+    pub fn set_sum_time_of(&mut self, method: TimingMethod, idx: usize, new: (u128, TimeType)) {
+        match method {
+            TimingMethod::RealTime => self.sum_times[idx] = new,
+            TimingMethod::GameTime => self.game_sum_times[idx] = new,
+        }
+    }
+    /// Set the history of attempts.
+    pub fn set_attempts(&mut self, new: &[Attempt]) {
+        self.attempts = new.to_owned();
+    }
+    /// Set the number of attempts that have been started. [`attempt_count`](Run::attempt_count)
+    /// never goes below the id of the last attempt in the history.
+    pub fn set_attempt_count(&mut self, new: u32) {
+        self.attempt_count = new;
+    }
+    /// Add an attempt to the end of the history.
+    pub fn push_attempt(&mut self, new: Attempt) {
+        self.attempts.push(new);
+    }
+    /// Remove the last attempt from the history and return it.
+    pub fn pop_attempt(&mut self) -> Option<Attempt> {
+        self.attempts.pop()
+    }
+    /// Add a segment to the end of the practice history.
+    pub fn push_practice(&mut self, new: PracticeSegment) {
+        self.practice.push(new);
+    }
+    /// Set the user-defined comparisons.
+    pub fn set_comparisons(&mut self, new: &[CustomComparison]) {
+        self.comparisons = new.to_owned();
+    }
+    /// Set the ghost run to race against. [`None`] removes the ghost.
+    pub fn set_ghost(&mut self, new: Option<CustomComparison>) {
+        self.ghost = new;
+    }
+    /// Set the goal time for the run. [`None`] removes it.
+    pub fn set_target(&mut self, new: Option<Target>) {
+        self.target = new;
+    }
+    /// Set the time limit for the timer to count down from. [`None`] makes the timer count up.
+    pub fn set_countdown(&mut self, new: Option<u128>) {
+        self.countdown = new;
+    }
+    /// Set the information identifying the run on speedrun.com.
+    pub fn set_metadata(&mut self, new: Metadata) {
+        self.metadata = new;
+    }
+    /// Set the notes for each split.
+    pub fn set_notes(&mut self, new: &[String]) {
+        self.notes = new.to_owned();
+    }
+    /// Set the groups that splits are organized into.
+    pub fn set_groups(&mut self, new: &[SegmentGroup]) {
+        self.groups = new.to_owned();
+    }
+    /// Set the best time of a single group as measured by `method`, specified by `idx`.
+    pub fn set_group_gold_of(&mut self, method: TimingMethod, idx: usize, new: TimeType) {
+        self.groups[idx].set_gold_time_of(method, new);
+    }
+    // convert a run from a split file or dump older than msf version 4 to nanoseconds
+    pub(crate) fn ms_to_ns(&mut self) {
+        let scale = |times: &mut Vec<TimeType>| times.iter_mut().for_each(|t| *t = t.ms_to_ns());
+        let scale_sums =
+            |sums: &mut Vec<(u128, TimeType)>| sums.iter_mut().for_each(|s| s.1 = s.1.ms_to_ns());
+        self.offset = self.offset.ms_to_ns();
+        self.pb = self.pb.ms_to_ns();
+        self.game_pb = self.game_pb.ms_to_ns();
+        scale(&mut self.pb_times);
+        scale(&mut self.gold_times);
+        scale(&mut self.game_pb_times);
+        scale(&mut self.game_gold_times);
+        scale_sums(&mut self.sum_times);
+        scale_sums(&mut self.game_sum_times);
+        let convert = |times: &[TimeType]| times.iter().map(|t| t.ms_to_ns()).collect::<Vec<_>>();
+        self.attempts = self
+            .attempts
+            .iter()
+            .map(|a| {
+                Attempt::new(
+                    a.id(),
+                    a.started(),
+                    a.ended(),
+                    &convert(a.times_of(TimingMethod::RealTime)),
+                    &convert(a.times_of(TimingMethod::GameTime)),
+                    a.pause_time() * NANOS_PER_MILLI,
+                )
+            })
+            .collect();
+        self.comparisons = self
+            .comparisons
+            .iter()
+            .map(|c| {
+                CustomComparison::new(
+                    c.name(),
+                    &convert(c.times_of(TimingMethod::RealTime)),
+                    &convert(c.times_of(TimingMethod::GameTime)),
+                )
+            })
+            .collect();
+        for group in &mut self.groups {
+            for method in [TimingMethod::RealTime, TimingMethod::GameTime] {
+                group.set_gold_time_of(method, group.gold_time_of(method).ms_to_ns());
+            }
+        }
+    }
 }
@@ -0,0 +1,60 @@
+use super::{TimeType, TimingMethod};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+/// A named group of consecutive splits, like a world containing its levels.
+pub struct SegmentGroup {
+    name: String,
+    start: usize,
+    end: usize,
+    #[serde(default)]
+    gold_time: TimeType,
+    #[serde(default)]
+    game_gold_time: TimeType,
+}
+
+impl SegmentGroup {
+    /// Create a new [`SegmentGroup`] containing the splits from `start` to `end`, inclusive.
+    pub fn new<S>(name: S, start: usize, end: usize) -> Self
+    where
+        S: ToString,
+    {
+        SegmentGroup {
+            name: name.to_string(),
+            start,
+            end,
+            gold_time: TimeType::None,
+            game_gold_time: TimeType::None,
+        }
+    }
+    /// Get the name of the group.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Get the index of the first split in the group.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+    /// Get the index of the last split in the group.
+    pub fn end(&self) -> usize {
+        self.end
+    }
+    /// Whether the split at `idx` is part of the group.
+    pub fn contains(&self, idx: usize) -> bool {
+        self.start <= idx && idx <= self.end
+    }
+    /// Get the best time the runner has achieved on the whole group as measured by `method`.
+    pub fn gold_time_of(&self, method: TimingMethod) -> TimeType {
+        match method {
+            TimingMethod::RealTime => self.gold_time,
+            TimingMethod::GameTime => self.game_gold_time,
+        }
+    }
+    /// Set the best time achieved on the whole group as measured by `method`.
+    pub fn set_gold_time_of(&mut self, method: TimingMethod, new: TimeType) {
+        match method {
+            TimingMethod::RealTime => self.gold_time = new,
+            TimingMethod::GameTime => self.game_gold_time = new,
+        }
+    }
+}
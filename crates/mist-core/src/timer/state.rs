@@ -3,34 +3,71 @@
 use super::dump::StateDump;
 use super::format;
 use super::Comparison as Comp;
-use super::MistInstant;
 use super::Run;
-use super::{DiffType, TimeType};
+use super::{Attempt, CustomComparison, DiffType, PracticeSegment, TimeType, TimingMethod};
+use super::{Clock, MistInstant, RunHandle, RunSnapshot};
 use serde::{Deserialize, Serialize};
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::{Cell, RefCell, RefMut},
+    rc::Rc,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 /// The state of the loaded run.
 ///
 /// This holds everything needed for timekeeping, updating the state of the run (e.g. paused/running),
 /// keeping comparisons, and storing times.
-pub struct RunState {
+///
+/// Time is read from a [`Clock`], which is a [`MistInstant`] unless another is given with
+/// [`with_clock`](RunState::with_clock).
+pub struct RunState<C: Clock = MistInstant> {
     run: Rc<RefCell<Run>>,
-    timer: MistInstant,
+    timer: C,
     timer_state: TimerState,
     run_status: SplitStatus,
     comparison: Comp,
+    method: TimingMethod,
     run_times: Vec<TimeType>,
+    game_run_times: Vec<TimeType>,
     run_diffs: Vec<DiffType>,
     run_golds: Vec<bool>,
+    game_run_golds: Vec<bool>,
     sum_comp_times: Vec<u128>,
     before_pause: u128,
     before_pause_split: u128,
     split: u128,
     start: u128,
     time: u128,
+    game_resume: u128,
+    game_split: u128,
+    game_time: u128,
+    game_paused: bool,
     current_split: usize,
+    attempt_start: u128,
+    pause_start: u128,
+    pause_time: u128,
     needs_save: bool,
     set_times: bool,
+    set_game_times: bool,
+    reset_snapshot: Option<StateDump>,
+    reset_golds: Vec<(TimingMethod, usize, TimeType)>,
+    reset_group_golds: Vec<(TimingMethod, usize, TimeType)>,
+    resumed_attempt: Option<u32>,
+    finish_snapshot: Option<Run>,
+    listeners: Vec<(ListenerId, Box<dyn StateListener>)>,
+    next_listener: usize,
+    handle: Option<RunHandle>,
+    generation: Cell<u64>,
+    published: Cell<u64>,
+    practice: Option<PracticeSession>,
+}
+
+// a practice session, started at split `start`
+struct PracticeSession {
+    start: usize,
+    update_golds: bool,
+    segments: Vec<PracticeSegment>,
 }
 
 #[derive(PartialEq, Debug)]
@@ -52,6 +89,9 @@ pub enum StateChangeRequest {
     /// Start the timer, advance a split, or stop the timer.
     Split,
     /// Undo a split, returning to the previous one.
+    ///
+    /// Unsplitting a finished run picks the timer back up as if the run had never stopped, so the
+    /// time since the final split counts towards both real time and game time.
     Unsplit,
     /// Skip a split, leaving it with no time.
     Skip,
@@ -59,6 +99,22 @@ pub enum StateChangeRequest {
     Reset,
     /// Change the comparison. `true` for next, `false` for prev.
     Comparison(bool),
+    /// Pause or unpause game time without affecting real time, e.g. during a load.
+    PauseGameTime,
+    /// Switch the timing method used for comparisons and the main timer.
+    SwitchTimingMethod,
+    /// Restore the timer and splits as they were just before the last reset, paused.
+    ///
+    /// Anything saved to the run by the reset, like the attempt and any golds that were kept,
+    /// stays. The attempt is updated when the resumed run ends.
+    UndoReset,
+    /// Save the new golds from the attempt that was just reset.
+    KeepResetGolds,
+    /// Start the timer at the split with index `split` to practice from there on.
+    ///
+    /// Segments timed while practicing are recorded to the run's practice history instead of
+    /// being an attempt, and never change the pb. New golds are only saved if `update_golds` is true.
+    Practice { split: usize, update_golds: bool },
 }
 
 /// A single change in state.
@@ -70,6 +126,11 @@ pub enum StateChange {
     EnterOffset, /*{amt: u128}*/
     /// Start timing forwards after an offset.
     ExitOffset,
+    /// Started practicing. Comes right before the [`EnterSplit`](StateChange::EnterSplit) for the first split practiced.
+    EnterPractice {
+        /// Index of split that practice started at.
+        idx: usize,
+    },
     /// Start a split.
     EnterSplit {
         /// Index of split that was entered.
@@ -85,6 +146,23 @@ pub enum StateChange {
         /// Difference from comparison.
         diff: i128,
     },
+    /// Finished a split while racing a ghost. Comes right after the [`ExitSplit`](StateChange::ExitSplit).
+    GhostDiff {
+        /// Index of split that was finished.
+        idx: usize,
+        /// Difference between the run so far and the ghost at the same split.
+        diff: i128,
+    },
+    /// Finished the last split of a [`SegmentGroup`](super::SegmentGroup).
+    ExitGroup {
+        /// Index of the group that was finished.
+        idx: usize,
+        status: SplitStatus,
+        /// Amount of time spent on the whole group.
+        time: u128,
+        /// Difference from the comparison's time for the group.
+        diff: i128,
+    },
     Pause,
     Unpause {
         status: SplitStatus,
@@ -97,8 +175,52 @@ pub enum StateChange {
     ComparisonChanged {
         comp: Comp,
     },
+    /// Game time stopped while real time keeps going.
+    PauseGameTime,
+    /// Game time started counting again.
+    UnpauseGameTime,
+    TimingMethodChanged {
+        method: TimingMethod,
+    },
+    /// The final split was undone, so the run is no longer finished.
+    ///
+    /// The pb, golds and sum times are rolled back to how they were before the run finished.
+    UndoFinish,
+    /// The golds from the last reset attempt were saved to the run.
+    ResetGoldsKept,
+    /// The last reset was undone, and the timer is paused in the restored state.
+    ResetUndone {
+        /// Snapshot of the state from just before the reset.
+        dump: Box<StateDump>,
+    },
+}
+
+/// Something that wants to know about changes to a [`RunState`].
+///
+/// Listeners are registered with [`subscribe`](RunState::subscribe), and are called from
+/// [`update`](RunState::update) once for every [`StateChange`] it produces, in order, along with the
+/// [`RunUpdate`] the change is part of. [`StateChange::None`] is never passed to a listener.
+///
+/// Any closure taking a `&StateChange` and a `&RunUpdate` is a listener.
+pub trait StateListener {
+    /// Called when the state changes.
+    fn on_change(&mut self, change: &StateChange, update: &RunUpdate);
+}
+
+impl<F> StateListener for F
+where
+    F: FnMut(&StateChange, &RunUpdate),
+{
+    fn on_change(&mut self, change: &StateChange, update: &RunUpdate) {
+        self(change, update)
+    }
 }
 
+/// Identifies a listener registered with [`subscribe`](RunState::subscribe) so that it can be
+/// removed later.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ListenerId(usize);
+
 /// Update returned from the [`RunState`].
 ///
 /// One of these is returned every frame when [`update`](RunState::update) is called.
@@ -112,10 +234,20 @@ pub struct RunUpdate {
     pub split_time: u128,
     /// Amount of time that has passed in the run as a whole.
     pub time: u128,
+    /// Amount of game time that has passed in the current split.
+    pub game_split_time: u128,
+    /// Amount of game time that has passed in the run as a whole.
+    pub game_time: u128,
     /// Whether the run is in an offset or not.
     pub offset: bool,
     /// Whether run is ahead, behind, etc.
     pub status: SplitStatus,
+    /// Time left before the run's [`countdown`](Run::countdown) runs out, if it has one.
+    ///
+    /// Stays at 0 once time is up.
+    pub remaining: Option<u128>,
+    /// Whether the countdown has run out on the run in progress.
+    pub time_up: bool,
 }
 
 /// Status of an active run.
@@ -140,27 +272,57 @@ pub enum SplitStatus {
 impl RunState {
     /// Create a new [`RunState`].
     pub fn new(run: Rc<RefCell<Run>>) -> Self {
-        let sum_comp_times = format::split_time_sum(&run.borrow().pb_times_u128());
+        Self::with_clock(run, MistInstant::now())
+    }
+}
+
+impl<C: Clock> RunState<C> {
+    /// Create a new [`RunState`] that reads time from `clock`.
+    pub fn with_clock(run: Rc<RefCell<Run>>, clock: C) -> Self {
         let len = run.borrow().pb_times().len();
-        Self {
+        let mut state = Self {
             run,
-            timer: MistInstant::now(),
+            timer: clock,
             timer_state: TimerState::NotRunning,
             comparison: Comp::PersonalBest,
+            method: TimingMethod::RealTime,
             run_status: SplitStatus::None,
             run_times: vec![TimeType::None; len],
+            game_run_times: vec![TimeType::None; len],
             run_diffs: vec![DiffType::Time(0); len],
             run_golds: vec![false; len],
-            sum_comp_times,
+            game_run_golds: vec![false; len],
+            sum_comp_times: vec![],
             before_pause: 0,
             before_pause_split: 0,
             split: 0,
             start: 0,
             time: 0,
+            game_resume: 0,
+            game_split: 0,
+            game_time: 0,
+            game_paused: false,
             current_split: 0,
+            attempt_start: 0,
+            pause_start: 0,
+            pause_time: 0,
             needs_save: false,
             set_times: false,
-        }
+            set_game_times: false,
+            reset_snapshot: None,
+            reset_golds: vec![],
+            reset_group_golds: vec![],
+            resumed_attempt: None,
+            finish_snapshot: None,
+            listeners: vec![],
+            next_listener: 0,
+            handle: None,
+            generation: Cell::new(0),
+            published: Cell::new(0),
+            practice: None,
+        };
+        state.rebuild_comp_times();
+        state
     }
 
     /// Fill in the RunState from a [`StateDump`].
@@ -168,18 +330,49 @@ impl RunState {
     /// Fills out all relevant fields of the RunState, and sets the timer state to
     /// paused. No other processing is necessary, on unpause everything should pick up properly.
     pub fn read_dump(&mut self, dump: &StateDump) {
-        self.run.replace(dump.run.clone());
+        // the ghost isn't part of the dump, so keep racing the one that is loaded
+        let ghost = self.run.borrow().ghost().cloned();
+        *self.run_mut() = dump.run.clone();
+        self.run_mut().set_ghost(ghost);
+        self.resumed_attempt = None;
+        self.read_timer_dump(dump);
+    }
+
+    // restore everything in `dump` except the run itself
+    fn read_timer_dump(&mut self, dump: &StateDump) {
         self.run_status = dump.status;
         self.comparison = dump.comparison;
+        self.method = dump.method;
+        let len = dump.run_times.len();
         self.run_times = dump.run_times.clone();
         self.run_diffs = dump.run_diffs.clone();
         self.run_golds = dump.run_golds.clone();
+        // dumps from before game time existed won't have these
+        self.game_run_times = if dump.game_run_times.len() == len {
+            dump.game_run_times.clone()
+        } else {
+            vec![TimeType::None; len]
+        };
+        self.game_run_golds = if dump.game_run_golds.len() == len {
+            dump.game_run_golds.clone()
+        } else {
+            vec![false; len]
+        };
         self.sum_comp_times = dump.sum_comp_times.clone();
         self.before_pause = dump.before_pause;
         self.before_pause_split = dump.before_pause_split;
         self.time = dump.time;
+        self.game_time = dump.game_time;
+        self.game_split = dump.game_split;
+        self.game_paused = dump.game_paused;
+        self.game_resume = self.timer.elapsed().as_nanos();
         self.current_split = dump.current_split;
+        self.attempt_start = dump.attempt_start;
+        self.pause_start = self.timer.elapsed().as_nanos();
+        self.pause_time = dump.pause_time;
         self.needs_save = dump.needs_save;
+        self.reset_golds.clear();
+        self.reset_group_golds.clear();
         self.timer_state = TimerState::Paused;
     }
 
@@ -191,21 +384,37 @@ impl RunState {
     /// illegal state changes from occurring by ignoring the request and returning [`StateChange::None`] in
     /// the [`RunUpdate`]
     pub fn update(&mut self, rq: &[StateChangeRequest]) -> RunUpdate {
-        let elapsed = self.timer.elapsed().as_millis();
+        let elapsed = self.timer.elapsed().as_nanos();
         if self.timer_state == TimerState::Running || self.timer_state == TimerState::Offset {
             self.time = (elapsed - self.start) + self.before_pause;
         }
+        if self.timer_state == TimerState::Running && !self.game_paused {
+            self.game_time += elapsed - self.game_resume;
+        }
+        self.game_resume = elapsed;
 
         // have to set pb times here or else the renderer sees them too early...
         if self.set_times {
-            self.run.borrow_mut().set_pb_times(&self.run_times);
+            self.run_mut().set_pb_times(&self.run_times);
             self.set_times = false;
         }
+        if self.set_game_times {
+            self.run_mut()
+                .set_pb_times_of(TimingMethod::GameTime, &self.game_run_times);
+            self.set_game_times = false;
+        }
 
-        let mut change = rq.iter().fold(Vec::new(), |mut vec, request| {
-            vec.append(&mut self.handle_scrq(request, elapsed));
-            vec
-        });
+        let queued = match self.handle {
+            Some(ref h) => h.take_requests(),
+            None => vec![],
+        };
+        let mut change = rq
+            .iter()
+            .chain(&queued)
+            .fold(Vec::new(), |mut vec, request| {
+                vec.append(&mut self.handle_scrq(request, elapsed));
+                vec
+            });
         if self.timer_state == TimerState::Offset && self.run.borrow().offset().val() <= self.time {
             self.timer_state = TimerState::Running;
             self.start = elapsed;
@@ -216,13 +425,109 @@ impl RunState {
         }
 
         self.calc_status();
-        RunUpdate {
+        let update = RunUpdate {
             change,
             split_time: (elapsed - self.split) + self.before_pause_split,
             time: self.time,
+            game_split_time: self.game_time - self.game_split,
+            game_time: self.game_time,
             offset: self.timer_state == TimerState::Offset,
             status: self.run_status,
+            remaining: self.remaining(),
+            time_up: self.time_up(),
+        };
+        if let Some(ref handle) = self.handle {
+            handle.publish(self.snapshot(
+                handle.snapshot().run,
+                update.split_time,
+                update.game_split_time,
+            ));
+        }
+        for (_, listener) in &mut self.listeners {
+            for change in &update.change {
+                if !matches!(change, StateChange::None) {
+                    listener.on_change(change, &update);
+                }
+            }
+        }
+        update
+    }
+
+    /// Get a [`RunHandle`] that other threads can use to watch and control this [`RunState`].
+    ///
+    /// All handles from the same [`RunState`] share the same snapshot and requests. The run in the
+    /// snapshot is only copied again when the [`RunState`] changes it, so changes made to the run
+    /// from outside are not seen until then.
+    pub fn handle(&mut self) -> RunHandle {
+        if self.handle.is_none() {
+            let run = Arc::new(self.run.borrow().clone());
+            let snapshot = self.snapshot(
+                run,
+                self.before_pause_split,
+                self.game_time - self.game_split,
+            );
+            self.handle = Some(RunHandle::new(snapshot));
         }
+        self.handle.clone().unwrap()
+    }
+
+    // only clones the run if it has been changed through `run_mut` since `last` was published
+    fn snapshot(&self, last: Arc<Run>, split_time: u128, game_split_time: u128) -> RunSnapshot {
+        let run = if self.published.get() == self.generation.get() {
+            last
+        } else {
+            self.published.set(self.generation.get());
+            Arc::new(self.run.borrow().clone())
+        };
+        RunSnapshot {
+            run,
+            split_time,
+            time: self.time,
+            game_split_time,
+            game_time: self.game_time,
+            current_split: self.current_split,
+            running: self.timer_state == TimerState::Running,
+            status: self.run_status,
+            comparison: self.comparison,
+            method: self.method,
+        }
+    }
+
+    // every change to the run goes through here so that snapshots know when to clone it
+    fn run_mut(&self) -> RefMut<'_, Run> {
+        self.generation.set(self.generation.get() + 1);
+        self.run.borrow_mut()
+    }
+
+    /// Register a [`StateListener`] to be told about every change in state from now on.
+    ///
+    /// Listeners are called in the order they were subscribed.
+    pub fn subscribe<L>(&mut self, listener: L) -> ListenerId
+    where
+        L: StateListener + 'static,
+    {
+        let id = ListenerId(self.next_listener);
+        self.next_listener += 1;
+        self.listeners.push((id, Box::new(listener)));
+        id
+    }
+
+    /// Remove a listener added with [`subscribe`](RunState::subscribe).
+    ///
+    /// Returns false if there was no listener with that id.
+    pub fn unsubscribe(&mut self, id: ListenerId) -> bool {
+        let len = self.listeners.len();
+        self.listeners.retain(|(l, _)| *l != id);
+        self.listeners.len() != len
+    }
+
+    /// Set the ghost run to race against, or remove it with [`None`](Option::None).
+    ///
+    /// The ghost is compared against at every split with a [`StateChange::GhostDiff`], and can
+    /// be picked as a comparison with [`Comparison::Ghost`](super::Comparison::Ghost).
+    pub fn set_ghost(&mut self, ghost: Option<CustomComparison>) {
+        self.run_mut().set_ghost(ghost);
+        self.rebuild_comp_times();
     }
 
     /// Whether the [`Run`] has been changed and needs to be saved.
@@ -230,35 +535,80 @@ impl RunState {
         self.needs_save
     }
 
+    /// Whether the attempt that was just reset had new golds that have not been saved.
+    ///
+    /// They can be kept with [`StateChangeRequest::KeepResetGolds`], and are thrown away once the
+    /// timer is started again.
+    pub fn has_reset_golds(&self) -> bool {
+        !self.reset_golds.is_empty() || !self.reset_group_golds.is_empty()
+    }
+
     /// Whether the timer is currently running.
     pub fn is_running(&self) -> bool {
         self.timer_state == TimerState::Running
     }
 
+    /// Whether an attempt is in progress, either running or paused.
+    ///
+    /// Practicing is not an attempt.
+    pub fn in_attempt(&self) -> bool {
+        matches!(self.timer_state, TimerState::Running | TimerState::Paused)
+            && !self.is_practicing()
+    }
+
+    /// Whether the timer was started with [`StateChangeRequest::Practice`] and has not been reset since.
+    pub fn is_practicing(&self) -> bool {
+        self.practice.is_some()
+    }
+
+    // time left on the countdown, which does not start until the offset is over
+    fn remaining(&self) -> Option<u128> {
+        if self.is_practicing() {
+            return Option::None;
+        }
+        let limit = self.run.borrow().countdown()?;
+        if self.timer_state == TimerState::Offset {
+            Some(limit)
+        } else {
+            Some(limit.saturating_sub(self.active_time()))
+        }
+    }
+
+    fn time_up(&self) -> bool {
+        self.remaining() == Some(0)
+            && matches!(
+                self.timer_state,
+                TimerState::Running | TimerState::Paused | TimerState::Finished
+            )
+    }
+
     fn calc_status(&mut self) {
         if self.comparison == Comp::None || self.timer_state != TimerState::Running {
             self.run_status = SplitStatus::None;
             return;
         }
         let run = self.run.borrow();
+        let current_time = self.active_time();
         if run.pb_times().is_empty() || run.pb_times().len() == 1 {
-            if run.pb().is_none() || self.time < run.pb().val() {
+            let pb = run.pb_of(self.method);
+            if pb.is_none() || current_time < pb.val() {
                 self.run_status = SplitStatus::Ahead;
             } else {
                 self.run_status = SplitStatus::Behind;
             }
         } else {
-            let buffer = if self.current_split != 0 {
+            let start = self.practice_start();
+            let buffer = if self.current_split > start {
                 self.run_diffs[self.current_split - 1].raw()
             } else {
                 0
             };
-            let allowed = self.sum_comp_times[self.current_split] as i128;
+            let allowed = self.comparison_since(start, self.current_split) as i128;
             if allowed == 0 {
                 self.run_status = SplitStatus::Ahead;
                 return;
             }
-            let time = self.time as i128;
+            let time = current_time as i128;
             // if the last split was ahead of comparison split
             if buffer < 0 {
                 let allowed = allowed - buffer;
@@ -300,116 +650,201 @@ impl RunState {
                 self.timer_state = TimerState::Paused;
                 self.before_pause = self.time;
                 self.before_pause_split += elapsed - self.split;
+                self.pause_start = elapsed;
                 return vec![StateChange::Pause];
             }
             Pause if self.timer_state == TimerState::Paused => {
                 self.timer_state = TimerState::Running;
                 self.start = elapsed;
                 self.split = elapsed;
+                self.pause_time += elapsed - self.pause_start;
                 return vec![StateChange::Unpause {
                     status: self.run_status,
                 }];
             }
+            Split if self.timer_state == TimerState::Running && self.is_practicing() => {
+                self.save_finish_snapshot();
+                let time = (elapsed - self.split) + self.before_pause_split;
+                let game_time = self.game_time - self.game_split;
+                self.split = elapsed;
+                self.before_pause_split = 0;
+                self.game_split = self.game_time;
+                let segment = match self.method {
+                    TimingMethod::RealTime => time,
+                    TimingMethod::GameTime => game_time,
+                };
+                let idx = self.current_split;
+                self.run_times[idx] = TimeType::Time(time);
+                self.game_run_times[idx] = TimeType::Time(game_time);
+                self.run_diffs[idx] =
+                    DiffType::Time(segment as i128 - self.comparison_segment(idx) as i128);
+                for &(method, time) in &[
+                    (TimingMethod::RealTime, time),
+                    (TimingMethod::GameTime, game_time),
+                ] {
+                    let gold = self.run.borrow().gold_times_of(method)[idx];
+                    if gold.is_none() || time < gold.val() {
+                        self.golds_of_mut(method)[idx] = true;
+                    }
+                }
+                if self.golds_of(self.method)[idx] {
+                    self.run_status = SplitStatus::Gold;
+                }
+                if let Some(ref mut practice) = self.practice {
+                    practice.segments.push(PracticeSegment::new(
+                        idx,
+                        now_timestamp(),
+                        time,
+                        game_time,
+                    ));
+                }
+                let start = self.practice_start();
+                let sum = self.times_of(self.method)[start..=idx]
+                    .iter()
+                    .map(|t| t.raw())
+                    .sum::<u128>();
+                let diff = sum as i128 - self.comparison_since(start, idx) as i128;
+                let mut changes = vec![StateChange::ExitSplit {
+                    idx,
+                    status: self.run_status,
+                    time: segment,
+                    diff,
+                }];
+                changes.extend(self.ghost_diff(idx));
+                changes.extend(self.exit_group(idx));
+                if idx == self.run.borrow().splits().len() - 1 {
+                    self.timer_state = TimerState::Finished;
+                    self.commit_practice();
+                    changes.push(StateChange::Finish);
+                } else {
+                    self.current_split += 1;
+                    changes.push(StateChange::EnterSplit {
+                        idx: self.current_split,
+                    });
+                }
+                return changes;
+            }
             Split if self.timer_state == TimerState::Running => {
+                self.save_finish_snapshot();
                 let time = (elapsed - self.split) + self.before_pause_split;
+                let game_time = self.game_time - self.game_split;
                 self.split = elapsed;
                 self.before_pause_split = 0;
+                self.game_split = self.game_time;
+                let segment = match self.method {
+                    TimingMethod::RealTime => time,
+                    TimingMethod::GameTime => game_time,
+                };
                 if self.run.borrow().splits().is_empty() {
-                    let diff = time as i128 - self.run.borrow().pb().val() as i128;
+                    let diff = segment as i128 - self.run.borrow().pb_of(self.method).val() as i128;
                     return vec![
                         StateChange::ExitSplit {
                             idx: self.current_split,
                             status: self.run_status,
-                            time,
+                            time: segment,
                             diff,
                         },
                         StateChange::Finish,
                     ];
                 }
                 self.run_times[self.current_split] = TimeType::Time(time);
-                self.run_diffs[self.current_split] =
-                    DiffType::Time(if self.comparison == Comp::PersonalBest {
-                        time as i128
-                            - self.run.borrow().pb_times()[self.current_split].val() as i128
-                    } else if self.comparison == Comp::Golds {
-                        time as i128
-                            - self.run.borrow().gold_times()[self.current_split].val() as i128
-                    } else if self.comparison == Comp::Average {
-                        let sum = self.run.borrow().sum_times()[self.current_split];
-                        time as i128
-                            - (sum.1 / {
-                                if sum.0 == 0 {
-                                    1
-                                } else {
-                                    sum.0
-                                }
-                            }) as i128
-                    } else {
-                        0
-                    });
-                let mut sum = self.run.borrow().sum_times()[self.current_split];
-                sum.0 += 1;
-                sum.1 += time;
-                self.run.borrow_mut().set_sum_time(self.current_split, sum);
+                self.game_run_times[self.current_split] = TimeType::Time(game_time);
+                self.run_diffs[self.current_split] = DiffType::Time(
+                    segment as i128 - self.comparison_segment(self.current_split) as i128,
+                );
+                for &(method, time) in &[
+                    (TimingMethod::RealTime, time),
+                    (TimingMethod::GameTime, game_time),
+                ] {
+                    let mut sum = self.run.borrow().sum_times_of(method)[self.current_split];
+                    sum.0 += 1;
+                    sum.1 += time;
+                    self.run_mut()
+                        .set_sum_time_of(method, self.current_split, sum);
+                    let gold = self.run.borrow().gold_times_of(method)[self.current_split];
+                    if gold.is_none() || time < gold.val() {
+                        let idx = self.current_split;
+                        self.golds_of_mut(method)[idx] = true;
+                    }
+                }
                 self.needs_save = true;
-                if self.run.borrow().gold_times()[self.current_split].is_none()
-                    || time < self.run.borrow().gold_times()[self.current_split].val()
-                {
-                    self.run_golds[self.current_split] = true;
+                if self.golds_of(self.method)[self.current_split] {
                     self.run_status = SplitStatus::Gold;
                 }
                 let sum = format::split_time_sum(
-                    &self.run_times.iter().map(|t| t.raw()).collect::<Vec<_>>(),
+                    &self
+                        .times_of(self.method)
+                        .iter()
+                        .map(|t| t.raw())
+                        .collect::<Vec<_>>(),
                 )[self.current_split];
-                let diff = sum as i128
-                    - format::split_time_sum(&self.run.borrow().pb_times_u128())[self.current_split]
-                        as i128;
+                let diff = sum as i128 - self.sum_comp_times[self.current_split] as i128;
                 if self.current_split == self.run.borrow().pb_times().len() - 1 {
-                    {
-                        let mut run = self.run.borrow_mut();
+                    for &method in &[TimingMethod::RealTime, TimingMethod::GameTime] {
+                        let mut run = self.run_mut();
                         for idx in self
-                            .run_golds
+                            .golds_of(method)
                             .iter()
                             .enumerate()
                             .filter_map(|(idx, &gold)| if gold { Some(idx) } else { Option::None })
                         {
-                            run.set_gold_time(idx, self.run_times[idx]);
+                            run.set_gold_time_of(method, idx, self.times_of(method)[idx]);
                         }
                     }
+                    self.commit_group_golds();
                     self.timer_state = TimerState::Finished;
+                    self.record_attempt();
                     if self.run.borrow().pb().is_none() || self.time < self.run.borrow().pb().val()
                     {
                         self.set_times = true;
-                        self.run.borrow_mut().set_pb(TimeType::Time(self.time));
+                        self.run_mut().set_pb(TimeType::Time(self.time));
                     }
-                    return vec![
-                        StateChange::ExitSplit {
-                            idx: self.current_split,
-                            status: self.run_status,
-                            time: self.run_times[self.current_split].val(),
-                            diff,
-                        },
-                        StateChange::Finish,
-                    ];
+                    let game_pb = self.run.borrow().pb_of(TimingMethod::GameTime);
+                    if game_pb.is_none() || self.game_time < game_pb.val() {
+                        self.set_game_times = true;
+                        self.run_mut()
+                            .set_pb_of(TimingMethod::GameTime, TimeType::Time(self.game_time));
+                    }
+                    let mut changes = vec![StateChange::ExitSplit {
+                        idx: self.current_split,
+                        status: self.run_status,
+                        time: segment,
+                        diff,
+                    }];
+                    changes.extend(self.ghost_diff(self.current_split));
+                    changes.extend(self.exit_group(self.current_split));
+                    changes.push(StateChange::Finish);
+                    return changes;
                 } else {
+                    let mut changes = vec![StateChange::ExitSplit {
+                        idx: self.current_split,
+                        status: self.run_status,
+                        time: segment,
+                        diff,
+                    }];
+                    changes.extend(self.ghost_diff(self.current_split));
+                    changes.extend(self.exit_group(self.current_split));
                     self.current_split += 1;
-                    return vec![
-                        StateChange::ExitSplit {
-                            idx: self.current_split - 1,
-                            status: self.run_status,
-                            time: self.run_times[self.current_split - 1].val(),
-                            diff,
-                        },
-                        StateChange::EnterSplit {
-                            idx: self.current_split,
-                        },
-                    ];
+                    changes.push(StateChange::EnterSplit {
+                        idx: self.current_split,
+                    });
+                    return changes;
                 }
             }
             Split if self.timer_state == TimerState::NotRunning => {
+                self.reset_snapshot = Option::None;
+                self.resumed_attempt = Option::None;
+                self.reset_golds.clear();
+                self.reset_group_golds.clear();
+                // history and pb may have changed since the comparison was last built
+                self.rebuild_comp_times();
                 self.start = elapsed;
                 self.split = elapsed;
                 self.time = 0;
+                self.game_split = 0;
+                self.game_time = 0;
+                self.attempt_start = now_timestamp();
+                self.pause_time = 0;
                 if self.run.borrow().offset().is_time() {
                     self.timer_state = TimerState::Offset;
                     return vec![StateChange::EnterOffset];
@@ -418,26 +853,122 @@ impl RunState {
                     return vec![StateChange::EnterSplit { idx: 0 }];
                 }
             }
-            Unsplit if self.timer_state == TimerState::Running && self.current_split != 0 => {
+            Practice {
+                split,
+                update_golds,
+            } if self.timer_state == TimerState::NotRunning
+                && *split < self.run.borrow().splits().len() =>
+            {
+                self.reset_snapshot = Option::None;
+                self.resumed_attempt = Option::None;
+                self.reset_golds.clear();
+                self.reset_group_golds.clear();
+                self.rebuild_comp_times();
+                self.start = elapsed;
+                self.split = elapsed;
+                self.time = 0;
+                self.game_split = 0;
+                self.game_time = 0;
+                self.pause_time = 0;
+                self.current_split = *split;
+                self.practice = Some(PracticeSession {
+                    start: *split,
+                    update_golds: *update_golds,
+                    segments: vec![],
+                });
+                self.timer_state = TimerState::Running;
+                return vec![
+                    StateChange::EnterPractice { idx: *split },
+                    StateChange::EnterSplit { idx: *split },
+                ];
+            }
+            Unsplit
+                if self.timer_state == TimerState::Running
+                    && self.current_split > self.practice_start() =>
+            {
                 self.current_split -= 1;
+                self.unrecord_practice(self.current_split);
                 self.before_pause_split = 0;
                 self.split -= self.run_times[self.current_split].raw();
+                self.game_split -= self.game_run_times[self.current_split].raw();
                 self.run_diffs[self.current_split] = DiffType::Time(0);
                 self.run_times[self.current_split] = TimeType::None;
+                self.game_run_times[self.current_split] = TimeType::None;
                 self.run_golds[self.current_split] = false;
+                self.game_run_golds[self.current_split] = false;
                 return vec![StateChange::EnterSplit {
                     idx: self.current_split,
                 }];
             }
+            Unsplit
+                if self.timer_state == TimerState::Finished && self.finish_snapshot.is_some() =>
+            {
+                let run = self.finish_snapshot.take().unwrap();
+                *self.run_mut() = run;
+                self.set_times = false;
+                self.set_game_times = false;
+                let idx = self.current_split;
+                self.unrecord_practice(idx);
+                // real time kept counting since the final split, so game time has to catch up
+                if !self.game_paused {
+                    self.game_time += elapsed - self.split;
+                }
+                self.before_pause_split = 0;
+                self.split -= self.run_times[idx].raw();
+                self.game_split -= self.game_run_times[idx].raw();
+                self.run_diffs[idx] = DiffType::Time(0);
+                self.run_times[idx] = TimeType::None;
+                self.game_run_times[idx] = TimeType::None;
+                self.run_golds[idx] = false;
+                self.game_run_golds[idx] = false;
+                self.timer_state = TimerState::Running;
+                return vec![StateChange::UndoFinish, StateChange::EnterSplit { idx }];
+            }
             Reset => {
+                self.finish_snapshot = Option::None;
+                let practicing = self.is_practicing();
+                if !practicing
+                    && matches!(self.timer_state, TimerState::Running | TimerState::Paused)
+                {
+                    let mut dump = self.create_state_dump();
+                    // undoing restores the run paused, so keep the time as if it was paused here
+                    if self.timer_state == TimerState::Running {
+                        dump.before_pause = self.time;
+                        dump.before_pause_split += elapsed - self.split;
+                    }
+                    self.reset_snapshot = Some(dump);
+                }
+                if self.timer_state == TimerState::Paused {
+                    self.pause_time += elapsed - self.pause_start;
+                }
+                self.reset_golds.clear();
+                self.reset_group_golds.clear();
+                if matches!(
+                    self.timer_state,
+                    TimerState::Running | TimerState::Paused | TimerState::Offset
+                ) {
+                    if practicing {
+                        self.commit_practice();
+                    } else {
+                        self.record_attempt();
+                        self.hold_reset_golds();
+                    }
+                }
+                self.practice = Option::None;
                 self.before_pause = 0;
                 self.before_pause_split = 0;
                 self.split = 0;
                 self.start = 0;
+                self.time = 0;
+                self.game_split = 0;
+                self.game_time = 0;
+                self.game_paused = false;
                 let len = self.run.borrow().pb_times().len();
                 self.run_diffs = vec![DiffType::Time(0); len];
                 self.run_times = vec![TimeType::None; len];
+                self.game_run_times = vec![TimeType::None; len];
                 self.run_golds = vec![false; len];
+                self.game_run_golds = vec![false; len];
                 self.current_split = 0;
                 self.timer_state = TimerState::NotRunning;
                 return vec![StateChange::Reset {
@@ -445,111 +976,369 @@ impl RunState {
                 }];
             }
             Skip if self.timer_state == TimerState::Running => {
+                self.save_finish_snapshot();
                 let time = (elapsed - self.split) + self.before_pause_split;
+                let game_time = self.game_time - self.game_split;
                 self.run_times[self.current_split] = TimeType::Skipped(time);
-                self.run_diffs[self.current_split] =
-                    DiffType::Skipped(if self.comparison == Comp::PersonalBest {
-                        time as i128
-                            - self.run.borrow().pb_times()[self.current_split].val() as i128
-                    } else if self.comparison == Comp::Golds {
-                        time as i128
-                            - self.run.borrow().gold_times()[self.current_split].val() as i128
-                    } else if self.comparison == Comp::Average {
-                        let sum = self.run.borrow().sum_times()[self.current_split];
-                        (time
-                            - (if sum.0 == 0 {
-                                sum.1 / sum.0
-                            } else {
-                                sum.1.val()
-                            })) as i128
-                    } else {
-                        0
-                    });
+                self.game_run_times[self.current_split] = TimeType::Skipped(game_time);
+                let segment = match self.method {
+                    TimingMethod::RealTime => time,
+                    TimingMethod::GameTime => game_time,
+                };
+                self.run_diffs[self.current_split] = DiffType::Skipped(
+                    segment as i128 - self.comparison_segment(self.current_split) as i128,
+                );
                 self.split = elapsed;
                 self.before_pause_split = 0;
+                self.game_split = self.game_time;
                 if self.current_split == self.run.borrow().pb_times().len() - 1 {
                     self.timer_state = TimerState::Finished;
-                    return vec![
-                        StateChange::ExitSplit {
-                            idx: self.current_split,
-                            status: self.run_status,
-                            time: 0,
-                            diff: 0,
-                        },
-                        StateChange::Finish,
-                    ];
+                    if self.is_practicing() {
+                        self.commit_practice();
+                    } else {
+                        self.record_attempt();
+                    }
+                    let mut changes = vec![StateChange::ExitSplit {
+                        idx: self.current_split,
+                        status: self.run_status,
+                        time: 0,
+                        diff: 0,
+                    }];
+                    changes.extend(self.exit_group(self.current_split));
+                    changes.push(StateChange::Finish);
+                    return changes;
                 } else {
+                    let mut changes = vec![StateChange::ExitSplit {
+                        idx: self.current_split,
+                        status: self.run_status,
+                        time: 0,
+                        diff: 0,
+                    }];
+                    changes.extend(self.exit_group(self.current_split));
                     self.current_split += 1;
-                    return vec![
-                        StateChange::ExitSplit {
-                            idx: self.current_split - 1,
-                            status: self.run_status,
-                            time: 0,
-                            diff: 0,
-                        },
-                        StateChange::EnterSplit {
-                            idx: self.current_split,
-                        },
-                    ];
+                    changes.push(StateChange::EnterSplit {
+                        idx: self.current_split,
+                    });
+                    return changes;
                 }
             }
             Comparison(n) => {
+                let run = self.run.borrow();
                 if *n {
-                    self.comparison.next();
+                    self.comparison.next(&run);
                 } else {
-                    self.comparison.prev();
-                }
-                match self.comparison {
-                    Comp::PersonalBest => {
-                        self.sum_comp_times =
-                            format::split_time_sum(&self.run.borrow().pb_times_u128());
-                    }
-                    Comp::Golds => {
-                        self.sum_comp_times =
-                            format::split_time_sum(&self.run.borrow().gold_times_u128());
-                    }
-                    Comp::Average => {
-                        self.sum_comp_times = format::split_time_sum(
-                            &self
-                                .run
-                                .borrow()
-                                .sum_times()
-                                .iter()
-                                .map(|&(n, t)| if n != 0 { t / n } else { t.val() })
-                                .collect::<Vec<_>>(),
-                        )
-                    }
-                    Comp::None => {
-                        self.sum_comp_times = vec![0; self.run.borrow().pb_times().len()];
-                    }
+                    self.comparison.prev(&run);
                 }
+                drop(run);
+                self.rebuild_comp_times();
                 return vec![StateChange::ComparisonChanged {
                     comp: self.comparison,
                 }];
             }
+            PauseGameTime
+                if matches!(
+                    self.timer_state,
+                    TimerState::Running | TimerState::Paused | TimerState::Offset
+                ) =>
+            {
+                self.game_paused = !self.game_paused;
+                if self.game_paused {
+                    return vec![StateChange::PauseGameTime];
+                } else {
+                    return vec![StateChange::UnpauseGameTime];
+                }
+            }
+            KeepResetGolds
+                if self.timer_state == TimerState::NotRunning && self.has_reset_golds() =>
+            {
+                let golds = std::mem::take(&mut self.reset_golds);
+                let group_golds = std::mem::take(&mut self.reset_group_golds);
+                let mut run = self.run_mut();
+                for (method, idx, time) in golds {
+                    run.set_gold_time_of(method, idx, time);
+                }
+                for (method, idx, time) in group_golds {
+                    run.set_group_gold_of(method, idx, time);
+                }
+                drop(run);
+                self.needs_save = true;
+                return vec![StateChange::ResetGoldsKept];
+            }
+            UndoReset
+                if self.timer_state == TimerState::NotRunning && self.reset_snapshot.is_some() =>
+            {
+                let dump = self.reset_snapshot.take().unwrap();
+                self.read_timer_dump(&dump);
+                self.resumed_attempt = self.run.borrow().attempts().last().map(|a| a.id());
+                // the reset recorded an attempt that has not been saved yet
+                self.needs_save = true;
+                return vec![StateChange::ResetUndone {
+                    dump: Box::new(dump),
+                }];
+            }
+            SwitchTimingMethod if self.timer_state == TimerState::NotRunning => {
+                self.method.toggle();
+                self.rebuild_comp_times();
+                return vec![StateChange::TimingMethodChanged {
+                    method: self.method,
+                }];
+            }
             _ => {}
         }
         vec![StateChange::None]
     }
 
+    fn active_time(&self) -> u128 {
+        match self.method {
+            TimingMethod::RealTime => self.time,
+            TimingMethod::GameTime => self.game_time,
+        }
+    }
+
+    fn times_of(&self, method: TimingMethod) -> &Vec<TimeType> {
+        match method {
+            TimingMethod::RealTime => &self.run_times,
+            TimingMethod::GameTime => &self.game_run_times,
+        }
+    }
+
+    fn golds_of(&self, method: TimingMethod) -> &Vec<bool> {
+        match method {
+            TimingMethod::RealTime => &self.run_golds,
+            TimingMethod::GameTime => &self.game_run_golds,
+        }
+    }
+
+    fn golds_of_mut(&mut self, method: TimingMethod) -> &mut Vec<bool> {
+        match method {
+            TimingMethod::RealTime => &mut self.run_golds,
+            TimingMethod::GameTime => &mut self.game_run_golds,
+        }
+    }
+
+    // time that the current comparison expects to be spent on split `idx`
+    fn comparison_segment(&self, idx: usize) -> u128 {
+        self.comparison.segments(&self.run.borrow(), self.method)[idx].raw()
+    }
+
+    // time the current comparison expects to be spent on splits `start` through `end`
+    fn comparison_since(&self, start: usize, end: usize) -> u128 {
+        let before = if start == 0 {
+            0
+        } else {
+            self.sum_comp_times[start - 1]
+        };
+        self.sum_comp_times[end] - before
+    }
+
+    fn rebuild_comp_times(&mut self) {
+        let segments = self.comparison.segments(&self.run.borrow(), self.method);
+        self.sum_comp_times =
+            format::split_time_sum(&segments.iter().map(|t| t.raw()).collect::<Vec<_>>());
+    }
+
+    // time spent on the whole group `idx` in the current attempt, if every split in it is done
+    fn group_time(&self, idx: usize, method: TimingMethod) -> TimeType {
+        let run = self.run.borrow();
+        let group = &run.groups()[idx];
+        let times = &self.times_of(method)[group.start()..=group.end()];
+        if times.iter().any(|t| t.is_none()) {
+            TimeType::None
+        } else {
+            TimeType::Time(times.iter().map(|t| t.raw()).sum())
+        }
+    }
+
+    // the group change to send if `split` is the last split of a group
+    fn exit_group(&self, split: usize) -> Option<StateChange> {
+        let idx = self.run.borrow().group_of(split)?;
+        let (start, end, gold) = {
+            let run = self.run.borrow();
+            let group = &run.groups()[idx];
+            (group.start(), group.end(), group.gold_time_of(self.method))
+        };
+        // groups only partly practiced have no time
+        if end != split || start < self.practice_start() {
+            return Option::None;
+        }
+        let time = self.group_time(idx, self.method).raw();
+        let comp = self.comparison.segments(&self.run.borrow(), self.method)[start..=end]
+            .iter()
+            .map(|t| t.raw())
+            .sum::<u128>();
+        let diff = time as i128 - comp as i128;
+        let status = if gold.is_none() || time < gold.val() {
+            SplitStatus::Gold
+        } else if diff < 0 {
+            SplitStatus::Ahead
+        } else {
+            SplitStatus::Behind
+        };
+        Some(StateChange::ExitGroup {
+            idx,
+            status,
+            time,
+            diff,
+        })
+    }
+
+    // difference between the run and the ghost as of the end of `split`, if the ghost reached it
+    fn ghost_diff(&self, split: usize) -> Option<StateChange> {
+        let run = self.run.borrow();
+        let ghost = run.ghost()?.times_of(self.method);
+        let start = self.practice_start();
+        let mut ghost_time = 0;
+        for idx in start..=split {
+            match ghost.get(idx) {
+                Some(t) if !t.is_none() => ghost_time += t.raw(),
+                _ => return Option::None,
+            }
+        }
+        let time = self.times_of(self.method)[start..=split]
+            .iter()
+            .map(|t| t.raw())
+            .sum::<u128>();
+        Some(StateChange::GhostDiff {
+            idx: split,
+            diff: time as i128 - ghost_time as i128,
+        })
+    }
+
+    // time of every completed group in the current attempt that beat its gold
+    fn new_group_golds(&self) -> Vec<(TimingMethod, usize, TimeType)> {
+        let mut golds = vec![];
+        for &method in &[TimingMethod::RealTime, TimingMethod::GameTime] {
+            for idx in 0..self.run.borrow().groups().len() {
+                let time = self.group_time(idx, method);
+                let gold = self.run.borrow().groups()[idx].gold_time_of(method);
+                if time.is_time() && (gold.is_none() || time.val() < gold.val()) {
+                    golds.push((method, idx, time));
+                }
+            }
+        }
+        golds
+    }
+
+    fn commit_group_golds(&mut self) {
+        for (method, idx, time) in self.new_group_golds() {
+            self.run_mut().set_group_gold_of(method, idx, time);
+        }
+    }
+
+    // set aside the golds of an unfinished attempt so that they can be kept after it is reset
+    fn hold_reset_golds(&mut self) {
+        for &method in &[TimingMethod::RealTime, TimingMethod::GameTime] {
+            let times = self.times_of(method);
+            let golds = self
+                .golds_of(method)
+                .iter()
+                .enumerate()
+                .filter(|&(_, &gold)| gold)
+                .map(|(idx, _)| (method, idx, times[idx]))
+                .collect::<Vec<_>>();
+            self.reset_golds.extend(golds);
+        }
+        self.reset_group_golds = self.new_group_golds();
+    }
+
+    // keep the run from before the final split so that finishing can be undone
+    fn save_finish_snapshot(&mut self) {
+        if self.current_split + 1 >= self.run.borrow().splits().len() {
+            self.finish_snapshot = Some(self.run.borrow().clone());
+        }
+    }
+
+    // first split timed in the current attempt or practice session
+    fn practice_start(&self) -> usize {
+        self.practice.as_ref().map_or(0, |p| p.start)
+    }
+
+    // store the practiced segments in the run's practice history, along with their golds if asked to
+    fn commit_practice(&mut self) {
+        let practice = match self.practice {
+            Some(ref p) => p,
+            Option::None => return,
+        };
+        let mut run = self.run_mut();
+        for segment in &practice.segments {
+            run.push_practice(segment.clone());
+        }
+        if practice.update_golds {
+            for &method in &[TimingMethod::RealTime, TimingMethod::GameTime] {
+                for idx in practice.start..=self.current_split {
+                    if self.golds_of(method)[idx] {
+                        run.set_gold_time_of(method, idx, self.times_of(method)[idx]);
+                    }
+                }
+            }
+        }
+        drop(run);
+        self.needs_save = true;
+    }
+
+    // forget the practiced segment for `split` after it is unsplit
+    fn unrecord_practice(&mut self, split: usize) {
+        if let Some(ref mut practice) = self.practice {
+            if matches!(practice.segments.last(), Some(s) if s.split() == split) {
+                practice.segments.pop();
+            }
+        }
+    }
+
+    // store the current attempt in the run's history, replacing the one recorded when it was
+    // reset if it was resumed with UndoReset
+    fn record_attempt(&mut self) {
+        let resumed = self.resumed_attempt.take();
+        let mut run = self.run_mut();
+        let id = match resumed {
+            Some(id) if run.attempts().last().map(|a| a.id()) == Some(id) => {
+                run.pop_attempt();
+                id
+            }
+            _ => run.next_attempt_id(),
+        };
+        let attempt = Attempt::new(
+            id,
+            self.attempt_start,
+            now_timestamp(),
+            &self.run_times,
+            &self.game_run_times,
+            self.pause_time,
+        );
+        run.push_attempt(attempt);
+        drop(run);
+        self.needs_save = true;
+    }
+
     /// Generate a [`StateDump`].
     ///
     /// Uses the current state of the timer to create a `StateDump` containing all
-    /// information available in the RunState. The fields unique to the renderer must be
-    /// filled by the application.
+    /// information available in the RunState. The fields unique to the renderer and the split
+    /// file the run came from must be filled by the application.
     pub fn create_state_dump(&self) -> StateDump {
         StateDump {
+            version: 2,
+            file: String::new(),
+            category: 0,
             run: self.run.borrow().clone(),
             status: self.run_status,
             comparison: self.comparison,
+            method: self.method,
             run_times: self.run_times.clone(),
+            game_run_times: self.game_run_times.clone(),
             run_diffs: self.run_diffs.clone(),
             run_golds: self.run_golds.clone(),
+            game_run_golds: self.game_run_golds.clone(),
             sum_comp_times: self.sum_comp_times.clone(),
             before_pause: self.before_pause,
             before_pause_split: self.before_pause_split,
             time: self.time,
+            game_split: self.game_split,
+            game_time: self.game_time,
+            game_paused: self.game_paused,
             current_split: self.current_split,
+            attempt_start: self.attempt_start,
+            pause_time: self.pause_time,
             needs_save: self.needs_save,
             top_index: 0,
             bottom_index: 0,
@@ -557,3 +1346,483 @@ impl RunState {
         }
     }
 }
+
+fn now_timestamp() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::ManualClock;
+    use std::time::Duration;
+    use StateChangeRequest::*;
+
+    fn new_state(splits: &[&str], offset: TimeType) -> (RunState<ManualClock>, ManualClock) {
+        let names = splits.iter().map(|&s| s.to_owned()).collect::<Vec<_>>();
+        let run = Run::new(
+            "test",
+            "test",
+            offset,
+            TimeType::None,
+            &names,
+            &vec![TimeType::None; names.len()],
+            &vec![TimeType::None; names.len()],
+            &vec![(0, TimeType::None); names.len()],
+        );
+        let clock = ManualClock::new();
+        let state = RunState::with_clock(Rc::new(RefCell::new(run)), clock.clone());
+        (state, clock)
+    }
+
+    // update the state at `ns` nanoseconds after the clock started
+    fn at(
+        state: &mut RunState<ManualClock>,
+        clock: &ManualClock,
+        ns: u64,
+        rq: &[StateChangeRequest],
+    ) -> RunUpdate {
+        clock.set(Duration::from_nanos(ns));
+        state.update(rq)
+    }
+
+    #[test]
+    fn test_splits() {
+        let (mut state, clock) = new_state(&["a", "b"], TimeType::None);
+        let update = at(&mut state, &clock, 0, &[Split]);
+        assert!(matches!(
+            update.change[..],
+            [StateChange::EnterSplit { idx: 0 }]
+        ));
+        let update = at(&mut state, &clock, 1000, &[Split]);
+        assert!(matches!(
+            update.change[..],
+            [
+                StateChange::ExitSplit {
+                    idx: 0,
+                    time: 1000,
+                    ..
+                },
+                StateChange::EnterSplit { idx: 1 }
+            ]
+        ));
+        let update = at(&mut state, &clock, 1200, &[]);
+        assert_eq!(update.time, 1200);
+        assert_eq!(update.split_time, 200);
+        let update = at(&mut state, &clock, 2500, &[Split]);
+        assert!(matches!(
+            update.change[..],
+            [
+                StateChange::ExitSplit {
+                    idx: 1,
+                    time: 1500,
+                    ..
+                },
+                StateChange::Finish
+            ]
+        ));
+        at(&mut state, &clock, 2600, &[]);
+        let run = state.run.borrow();
+        assert_eq!(run.pb(), TimeType::Time(2500));
+        assert_eq!(
+            run.pb_times(),
+            &vec![TimeType::Time(1000), TimeType::Time(1500)]
+        );
+        assert_eq!(run.gold_times(), run.pb_times());
+        assert_eq!(run.sum_times()[1], (1, TimeType::Time(1500)));
+        assert_eq!(run.attempts().len(), 1);
+        assert_eq!(run.attempts()[0].time(), TimeType::Time(2500));
+    }
+
+    #[test]
+    fn test_pause() {
+        let (mut state, clock) = new_state(&["a"], TimeType::None);
+        at(&mut state, &clock, 0, &[Split]);
+        let update = at(&mut state, &clock, 1000, &[Pause]);
+        assert!(matches!(update.change[..], [StateChange::Pause]));
+        let update = at(&mut state, &clock, 2500, &[]);
+        assert_eq!(update.time, 1000);
+        at(&mut state, &clock, 3000, &[Pause]);
+        let update = at(&mut state, &clock, 3500, &[]);
+        assert_eq!(update.time, 1500);
+        assert_eq!(update.split_time, 1500);
+        let update = at(&mut state, &clock, 4000, &[Split]);
+        assert!(matches!(
+            update.change[..],
+            [
+                StateChange::ExitSplit { time: 2000, .. },
+                StateChange::Finish
+            ]
+        ));
+        let run = state.run.borrow();
+        assert_eq!(run.pb(), TimeType::Time(2000));
+        assert_eq!(run.attempts()[0].pause_time(), 2000);
+    }
+
+    #[test]
+    fn test_offset() {
+        let (mut state, clock) = new_state(&["a"], TimeType::Time(500));
+        let update = at(&mut state, &clock, 0, &[Split]);
+        assert!(matches!(update.change[..], [StateChange::EnterOffset]));
+        let update = at(&mut state, &clock, 300, &[]);
+        assert!(update.offset);
+        assert_eq!(update.time, 300);
+        let update = at(&mut state, &clock, 600, &[]);
+        assert!(!update.offset);
+        assert!(matches!(
+            update.change[..],
+            [StateChange::ExitOffset, StateChange::EnterSplit { idx: 0 }]
+        ));
+        let update = at(&mut state, &clock, 1600, &[Split]);
+        assert!(matches!(
+            update.change[..],
+            [
+                StateChange::ExitSplit { time: 1000, .. },
+                StateChange::Finish
+            ]
+        ));
+        assert_eq!(state.run.borrow().pb(), TimeType::Time(1000));
+    }
+
+    #[test]
+    fn test_countdown() {
+        let (mut state, clock) = new_state(&["a", "b"], TimeType::None);
+        assert_eq!(at(&mut state, &clock, 0, &[]).remaining, Option::None);
+        state.run.borrow_mut().set_countdown(Some(1500));
+        let update = at(&mut state, &clock, 0, &[Split]);
+        assert_eq!(update.remaining, Some(1500));
+        let update = at(&mut state, &clock, 1000, &[Split]);
+        assert_eq!(update.remaining, Some(500));
+        assert!(!update.time_up);
+        let update = at(&mut state, &clock, 1800, &[]);
+        assert_eq!(update.remaining, Some(0));
+        assert!(update.time_up);
+        let update = at(&mut state, &clock, 2000, &[Split]);
+        assert!(matches!(
+            update.change[..],
+            [
+                StateChange::ExitSplit { time: 1000, .. },
+                StateChange::Finish
+            ]
+        ));
+        assert!(update.time_up);
+        assert_eq!(state.run.borrow().pb(), TimeType::Time(2000));
+        let update = at(&mut state, &clock, 2500, &[Reset]);
+        assert_eq!(update.remaining, Some(1500));
+        assert!(!update.time_up);
+    }
+
+    #[test]
+    fn test_unsplit() {
+        let (mut state, clock) = new_state(&["a", "b"], TimeType::None);
+        at(&mut state, &clock, 0, &[Split]);
+        at(&mut state, &clock, 1000, &[Split]);
+        let update = at(&mut state, &clock, 1500, &[Unsplit]);
+        assert!(matches!(
+            update.change[..],
+            [StateChange::EnterSplit { idx: 0 }]
+        ));
+        assert_eq!(state.run_times[0], TimeType::None);
+        assert_eq!(update.split_time, 1500);
+        let update = at(&mut state, &clock, 2000, &[Split]);
+        assert!(matches!(
+            update.change[0],
+            StateChange::ExitSplit {
+                idx: 0,
+                time: 2000,
+                ..
+            }
+        ));
+        // the first split can't be unsplit
+        let (mut state, clock) = new_state(&["a", "b"], TimeType::None);
+        at(&mut state, &clock, 0, &[Split]);
+        let update = at(&mut state, &clock, 500, &[Unsplit]);
+        assert!(matches!(update.change[..], [StateChange::None]));
+    }
+
+    #[test]
+    fn test_game_time() {
+        let (mut state, clock) = new_state(&["a"], TimeType::None);
+        at(&mut state, &clock, 0, &[Split]);
+        let update = at(&mut state, &clock, 1000, &[PauseGameTime]);
+        assert!(matches!(update.change[..], [StateChange::PauseGameTime]));
+        let update = at(&mut state, &clock, 3000, &[PauseGameTime]);
+        assert_eq!(update.game_time, 1000);
+        assert_eq!(update.time, 3000);
+        at(&mut state, &clock, 4000, &[Split]);
+        let run = state.run.borrow();
+        assert_eq!(run.pb_of(TimingMethod::RealTime), TimeType::Time(4000));
+        assert_eq!(run.pb_of(TimingMethod::GameTime), TimeType::Time(2000));
+    }
+
+    #[test]
+    fn test_reset() {
+        let (mut state, clock) = new_state(&["a", "b"], TimeType::None);
+        at(&mut state, &clock, 0, &[Split]);
+        at(&mut state, &clock, 1000, &[Split]);
+        let update = at(&mut state, &clock, 1500, &[Reset]);
+        assert!(matches!(update.change[..], [StateChange::Reset { .. }]));
+        assert!(state.has_reset_golds());
+        assert_eq!(state.run.borrow().attempts().len(), 1);
+        assert!(state.run.borrow().gold_times()[0].is_none());
+        let update = at(&mut state, &clock, 1600, &[KeepResetGolds]);
+        assert!(matches!(update.change[..], [StateChange::ResetGoldsKept]));
+        assert_eq!(state.run.borrow().gold_times()[0], TimeType::Time(1000));
+        assert!(state.run.borrow().pb().is_none());
+        assert!(!state.has_reset_golds());
+    }
+
+    #[test]
+    fn test_undo_reset() {
+        let (mut state, clock) = new_state(&["a", "b"], TimeType::None);
+        at(&mut state, &clock, 0, &[Split]);
+        at(&mut state, &clock, 1000, &[Split]);
+        at(&mut state, &clock, 1500, &[Reset, KeepResetGolds]);
+        let update = at(&mut state, &clock, 1600, &[UndoReset]);
+        assert!(matches!(
+            update.change[..],
+            [StateChange::ResetUndone { .. }]
+        ));
+        assert_eq!(state.run.borrow().gold_times()[0], TimeType::Time(1000));
+        assert_eq!(state.run.borrow().attempts().len(), 1);
+        assert!(state.needs_save());
+        at(&mut state, &clock, 2000, &[Pause]);
+        let update = at(&mut state, &clock, 2500, &[Split]);
+        assert!(matches!(
+            update.change[..],
+            [
+                StateChange::ExitSplit {
+                    idx: 1,
+                    time: 1000,
+                    ..
+                },
+                StateChange::Finish
+            ]
+        ));
+        let run = state.run.borrow();
+        assert_eq!(run.pb(), TimeType::Time(2000));
+        assert_eq!(run.gold_times()[0], TimeType::Time(1000));
+        assert_eq!(run.attempts().len(), 1);
+        assert_eq!(run.attempts()[0].id(), 1);
+        assert_eq!(run.attempts()[0].time(), TimeType::Time(2000));
+    }
+
+    #[test]
+    fn test_unsplit_finished() {
+        let (mut state, clock) = new_state(&["a", "b"], TimeType::None);
+        at(&mut state, &clock, 0, &[Split]);
+        at(&mut state, &clock, 1000, &[Split]);
+        at(&mut state, &clock, 2000, &[Split]);
+        at(&mut state, &clock, 2100, &[]);
+        assert_eq!(state.run.borrow().pb(), TimeType::Time(2000));
+        let update = at(&mut state, &clock, 2500, &[Unsplit]);
+        assert!(matches!(
+            update.change[..],
+            [StateChange::UndoFinish, StateChange::EnterSplit { idx: 1 }]
+        ));
+        {
+            let run = state.run.borrow();
+            assert!(run.pb().is_none());
+            assert!(run.pb_times()[1].is_none());
+            assert!(run.gold_times()[1].is_none());
+            assert_eq!(run.sum_times()[1], (0, TimeType::None));
+            assert!(run.attempts().is_empty());
+        }
+        // the timer picks up as if the run never stopped
+        let update = at(&mut state, &clock, 2600, &[]);
+        assert_eq!(update.time, 2600);
+        assert_eq!(update.split_time, 1600);
+        assert_eq!(update.game_time, 2600);
+        assert_eq!(update.game_split_time, 1600);
+        let update = at(&mut state, &clock, 3000, &[Split]);
+        assert!(matches!(
+            update.change[..],
+            [
+                StateChange::ExitSplit {
+                    idx: 1,
+                    time: 2000,
+                    ..
+                },
+                StateChange::Finish
+            ]
+        ));
+        assert_eq!(state.run.borrow().pb(), TimeType::Time(3000));
+        assert_eq!(
+            state.run.borrow().pb_of(TimingMethod::GameTime),
+            TimeType::Time(3000)
+        );
+        assert_eq!(state.run.borrow().attempts().len(), 1);
+    }
+
+    #[test]
+    fn test_listeners() {
+        let (mut state, clock) = new_state(&["a", "b"], TimeType::None);
+        let seen = Rc::new(RefCell::new(vec![]));
+        let sink = Rc::clone(&seen);
+        let id = state.subscribe(move |change: &StateChange, update: &RunUpdate| {
+            sink.borrow_mut().push(match change {
+                StateChange::EnterSplit { idx } => format!("enter {} at {}", idx, update.time),
+                StateChange::ExitSplit { idx, .. } => format!("exit {}", idx),
+                c => format!("{:?}", c),
+            });
+        });
+        at(&mut state, &clock, 0, &[Split]);
+        // nothing to unsplit yet, so nothing is sent
+        at(&mut state, &clock, 500, &[Unsplit, Unsplit]);
+        at(&mut state, &clock, 1000, &[Split]);
+        assert_eq!(
+            *seen.borrow(),
+            vec!["enter 0 at 0", "exit 0", "enter 1 at 1000"]
+        );
+        assert!(state.unsubscribe(id));
+        assert!(!state.unsubscribe(id));
+        at(&mut state, &clock, 2000, &[Split]);
+        assert_eq!(seen.borrow().len(), 3);
+    }
+
+    #[test]
+    fn test_handle() {
+        let (mut state, clock) = new_state(&["a", "b"], TimeType::None);
+        let handle = state.handle();
+        let watcher = handle.clone();
+        let before = handle.snapshot().run;
+        at(&mut state, &clock, 0, &[]);
+        assert!(Arc::ptr_eq(&before, &watcher.snapshot().run));
+        let other = handle.clone();
+        std::thread::spawn(move || other.request(Split))
+            .join()
+            .unwrap();
+        at(&mut state, &clock, 0, &[]);
+        at(&mut state, &clock, 1000, &[]);
+        let snapshot = std::thread::spawn(move || handle.snapshot())
+            .join()
+            .unwrap();
+        assert!(snapshot.running);
+        assert_eq!(snapshot.time, 1000);
+        assert_eq!(snapshot.current_split, 0);
+        assert_eq!(snapshot.run.splits(), &vec!["a".to_owned(), "b".to_owned()]);
+        at(&mut state, &clock, 1500, &[Split]);
+        assert!(!Arc::ptr_eq(&before, &watcher.snapshot().run));
+        assert_eq!(
+            watcher.snapshot().run.sum_times()[0],
+            (1, TimeType::Time(1500))
+        );
+    }
+
+    #[test]
+    fn test_ghost() {
+        let (mut state, clock) = new_state(&["a", "b"], TimeType::None);
+        let ghost = CustomComparison::new(
+            "ghost",
+            &[TimeType::Time(800), TimeType::None],
+            &[TimeType::None, TimeType::None],
+        );
+        state.set_ghost(Some(ghost));
+        at(&mut state, &clock, 0, &[Split]);
+        let update = at(&mut state, &clock, 1000, &[Split]);
+        assert!(matches!(
+            update.change[..],
+            [
+                StateChange::ExitSplit { idx: 0, .. },
+                StateChange::GhostDiff { idx: 0, diff: 200 },
+                StateChange::EnterSplit { idx: 1 }
+            ]
+        ));
+        // the ghost never reached the last split
+        let update = at(&mut state, &clock, 2000, &[Split]);
+        assert!(matches!(
+            update.change[..],
+            [StateChange::ExitSplit { idx: 1, .. }, StateChange::Finish]
+        ));
+        at(&mut state, &clock, 3000, &[Reset]);
+        // the ghost can only be picked once it is loaded
+        let update = at(
+            &mut state,
+            &clock,
+            3000,
+            &[Comparison(false), Comparison(false), Comparison(false)],
+        );
+        assert!(matches!(
+            update.change[..],
+            [
+                StateChange::ComparisonChanged {
+                    comp: Comp::Average
+                },
+                StateChange::ComparisonChanged { comp: Comp::None },
+                StateChange::ComparisonChanged { comp: Comp::Ghost }
+            ]
+        ));
+        assert_eq!(state.comparison_segment(0), 800);
+    }
+
+    #[test]
+    fn test_practice() {
+        let (mut state, clock) = new_state(&["a", "b", "c"], TimeType::None);
+        let update = at(
+            &mut state,
+            &clock,
+            0,
+            &[Practice {
+                split: 1,
+                update_golds: false,
+            }],
+        );
+        assert!(matches!(
+            update.change[..],
+            [
+                StateChange::EnterPractice { idx: 1 },
+                StateChange::EnterSplit { idx: 1 }
+            ]
+        ));
+        assert!(state.is_practicing());
+        assert!(!state.in_attempt());
+        // can't unsplit past where practice started
+        let update = at(&mut state, &clock, 100, &[Unsplit]);
+        assert!(matches!(update.change[..], [StateChange::None]));
+        at(&mut state, &clock, 500, &[Split]);
+        let update = at(&mut state, &clock, 1200, &[Split]);
+        assert!(matches!(
+            update.change[..],
+            [
+                StateChange::ExitSplit {
+                    idx: 2,
+                    time: 700,
+                    status: SplitStatus::Gold,
+                    ..
+                },
+                StateChange::Finish
+            ]
+        ));
+        at(&mut state, &clock, 1300, &[Reset]);
+        assert!(!state.is_practicing());
+        {
+            let run = state.run.borrow();
+            assert_eq!(run.practice().len(), 2);
+            assert_eq!(run.practice()[0].split(), 1);
+            assert_eq!(run.practice()[0].time(), 500);
+            assert_eq!(run.gold_times(), &vec![TimeType::None; 3]);
+            assert_eq!(run.pb(), TimeType::None);
+            assert_eq!(run.pb_times(), &vec![TimeType::None; 3]);
+            assert!(run.attempts().is_empty());
+        }
+        at(
+            &mut state,
+            &clock,
+            2000,
+            &[Practice {
+                split: 2,
+                update_golds: true,
+            }],
+        );
+        at(&mut state, &clock, 2300, &[Split]);
+        at(&mut state, &clock, 2400, &[Reset]);
+        let run = state.run.borrow();
+        assert_eq!(run.practice().len(), 3);
+        assert_eq!(run.gold_times()[2], TimeType::Time(300));
+        assert_eq!(run.pb_times(), &vec![TimeType::None; 3]);
+        assert!(run.attempts().is_empty());
+    }
+}
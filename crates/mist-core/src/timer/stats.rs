@@ -0,0 +1,134 @@
+//! Statistics about how consistent each segment of a [`Run`] is, from its attempt history.
+use super::generate::{percentile, segment_history};
+use super::{Run, TimeType, TimingMethod};
+
+/// Statistics for a single split over every recorded attempt.
+///
+/// Times are in nanoseconds, and are `None` if no attempt has a time for the split.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentStats {
+    /// Number of attempts that got to the split.
+    pub reached: usize,
+    /// Number of attempts that were reset on the split.
+    pub resets: usize,
+    /// Average time spent on the split.
+    pub mean: Option<f64>,
+    /// Median time spent on the split.
+    pub median: Option<f64>,
+    /// Population standard deviation of the time spent on the split.
+    pub std_dev: Option<f64>,
+    /// Time that could be saved on the split compared to the pb, i.e. pb segment minus gold.
+    pub time_save: Option<u128>,
+    // sorted times of every attempt that completed the split without skipping it
+    history: Vec<f64>,
+}
+
+impl SegmentStats {
+    /// Get the time at percentile `p` (from 0 to 1) of the attempts that completed the split.
+    ///
+    /// For example `percentile(0.9)` is the time that 90% of completed segments were faster than.
+    pub fn percentile(&self, p: f64) -> Option<f64> {
+        percentile(&self.history, p.clamp(0.0, 1.0))
+    }
+    /// Fraction of the attempts that got to the split that were reset on it.
+    pub fn reset_rate(&self) -> f64 {
+        if self.reached == 0 {
+            0.0
+        } else {
+            self.resets as f64 / self.reached as f64
+        }
+    }
+    /// Every completed, unskipped time of the split, fastest first.
+    pub fn history(&self) -> &[f64] {
+        &self.history
+    }
+}
+
+/// Calculate [`SegmentStats`] for every split in `run` as measured by `method`.
+pub fn segment_stats(run: &Run, method: TimingMethod) -> Vec<SegmentStats> {
+    let len = run.splits().len();
+    let mut reached = vec![0; len];
+    let mut resets = vec![0; len];
+    for attempt in run.attempts() {
+        let times = attempt.times_of(method);
+        for i in 0..len {
+            reached[i] += 1;
+            if matches!(times.get(i), None | Some(TimeType::None)) {
+                // attempts that never got to the end stop at the split they were reset on
+                if !attempt.is_finished() {
+                    resets[i] += 1;
+                }
+                break;
+            }
+        }
+    }
+    let pb_times = run.pb_times_of(method);
+    let golds = run.gold_times_of(method);
+    segment_history(run, method)
+        .into_iter()
+        .enumerate()
+        .map(|(i, history)| {
+            let (mean, std_dev) = if history.is_empty() {
+                (None, None)
+            } else {
+                let n = history.len() as f64;
+                let mean = history.iter().sum::<f64>() / n;
+                let var = history.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / n;
+                (Some(mean), Some(var.sqrt()))
+            };
+            let time_save = match (pb_times.get(i), golds.get(i)) {
+                (Some(pb), Some(gold)) if pb.is_time() && gold.is_time() => {
+                    Some(pb.val().saturating_sub(gold.val()))
+                }
+                _ => None,
+            };
+            SegmentStats {
+                reached: reached[i],
+                resets: resets[i],
+                mean,
+                median: percentile(&history, 0.5),
+                std_dev,
+                time_save,
+                history,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::{Attempt, TimeType::*, TimingMethod::RealTime};
+
+    #[test]
+    fn test_segment_stats() {
+        let mut run = Run::new(
+            "test",
+            "test",
+            None,
+            Time(300),
+            &["a".into(), "b".into()],
+            &[Time(100), Time(200)],
+            &[Time(90), Time(150)],
+            &[(3, Time(360)), (2, Time(350))],
+        );
+        run.set_attempts(&[
+            Attempt::new(1, 0, 0, &[Time(100), Time(200)], &[], 0),
+            Attempt::new(2, 0, 0, &[Time(90), Time(250)], &[], 0),
+            Attempt::new(3, 0, 0, &[Time(170), None], &[], 0),
+            Attempt::new(4, 0, 0, &[None, None], &[], 0),
+        ]);
+        let stats = segment_stats(&run, RealTime);
+        assert_eq!(stats[0].reached, 4);
+        assert_eq!(stats[0].resets, 1);
+        assert_eq!(stats[1].reached, 3);
+        assert_eq!(stats[1].resets, 1);
+        assert!((stats[1].reset_rate() - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats[0].mean, Some(120.0));
+        assert_eq!(stats[0].median, Some(100.0));
+        assert_eq!(stats[1].std_dev, Some(25.0));
+        assert_eq!(stats[1].percentile(1.0), Some(250.0));
+        assert_eq!(stats[0].time_save, Some(10));
+        assert_eq!(stats[1].time_save, Some(50));
+    }
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+/// A goal time for a whole [`Run`](super::Run), like sub 1:30:00, to be spread over its splits and compared against.
+pub struct Target {
+    time: u128,
+    #[serde(default)]
+    basis: TargetBasis,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+/// What a [`Target`] is spread over the splits in proportion to.
+pub enum TargetBasis {
+    /// The best time on each split.
+    #[default]
+    Golds,
+    /// The time spent on each split in the pb.
+    PersonalBest,
+}
+
+impl Target {
+    /// Create a new [`Target`] of `time` for the whole run.
+    pub fn new(time: u128, basis: TargetBasis) -> Self {
+        Target { time, basis }
+    }
+    /// Get the goal time for the whole run.
+    pub fn time(&self) -> u128 {
+        self.time
+    }
+    /// Get what the goal is spread over the splits in proportion to.
+    pub fn basis(&self) -> TargetBasis {
+        self.basis
+    }
+}
@@ -1,6 +1,12 @@
 use serde::{Deserialize, Serialize};
 use std::ops::{AddAssign, Div, SubAssign};
 
+/// Number of nanoseconds in a millisecond.
+///
+/// Times are stored in nanoseconds, but split files from before msf version 4 stored them in
+/// milliseconds.
+pub const NANOS_PER_MILLI: u128 = 1_000_000;
+
 #[derive(PartialEq, Copy, Clone, Serialize, Deserialize, Debug)]
 /// A type of time recorded with the timer.
 pub enum TimeType {
@@ -24,6 +30,16 @@ pub enum DiffType {
     Time(i128),
 }
 
+#[derive(PartialEq, Eq, Copy, Clone, Serialize, Deserialize, Debug, Default)]
+/// The clock that a time was measured with.
+pub enum TimingMethod {
+    /// Wall-clock time, which only stops when the timer is paused.
+    #[default]
+    RealTime,
+    /// Load-removed time, which can be paused separately from real time.
+    GameTime,
+}
+
 impl TimeType {
     /// Convert from a `u128` to `TimeType`.
     /// If `time` is 0, returns `None`, otherwise returns
@@ -81,6 +97,14 @@ impl TimeType {
     pub fn is_none(self) -> bool {
         self == TimeType::None
     }
+    // times from before msf version 4 are in milliseconds
+    pub(crate) fn ms_to_ns(self) -> Self {
+        match self {
+            TimeType::Skipped(x) => TimeType::Skipped(x * NANOS_PER_MILLI),
+            TimeType::Time(x) => TimeType::Time(x * NANOS_PER_MILLI),
+            TimeType::None => TimeType::None,
+        }
+    }
 }
 
 impl From<u128> for TimeType {
@@ -110,6 +134,12 @@ impl DiffType {
         let (DiffType::Skipped(x) | DiffType::Time(x)) = self;
         x
     }
+    pub(crate) fn ms_to_ns(self) -> Self {
+        match self {
+            DiffType::Skipped(x) => DiffType::Skipped(x * NANOS_PER_MILLI as i128),
+            DiffType::Time(x) => DiffType::Time(x * NANOS_PER_MILLI as i128),
+        }
+    }
 }
 
 impl SubAssign<u128> for TimeType {
@@ -144,6 +174,16 @@ impl Div<u128> for TimeType {
     }
 }
 
+impl TimingMethod {
+    /// Switch to the other timing method.
+    pub fn toggle(&mut self) {
+        *self = match self {
+            TimingMethod::RealTime => TimingMethod::GameTime,
+            TimingMethod::GameTime => TimingMethod::RealTime,
+        }
+    }
+}
+
 impl Default for TimeType {
     fn default() -> Self {
         Self::None
@@ -1,26 +1,44 @@
 use fltk::{
     app, button, dialog, draw,
     enums::{Align, Color, Font, FrameType},
-    input,
+    input, menu,
     prelude::*,
     table,
     window::*,
 };
 use lazy_static::lazy_static;
-use mist_core::{parse::LssParser, parse::MsfParser, timing::ms_to_readable, Run};
-use regex::Regex;
+use mist_core::{
+    error::ParseError,
+    parse::{LssParser, MsfParser},
+    timer::{
+        format::{ns_split_time_text, ns_to_readable, readable_to_ns},
+        stats::segment_stats,
+        Categories, Run, Target, TargetBasis, TimeType, TimingMethod,
+    },
+};
 use std::convert::TryInto;
 use std::sync::Mutex;
 use tinyfiledialogs as tfd;
 
 static HEADERS: [&'static str; 3] = ["Split Name", "Personal Best", "Gold"];
+static STATS_HEADERS: [&str; 8] = [
+    "Split Name",
+    "Mean",
+    "Median",
+    "Std Dev",
+    "10th %",
+    "90th %",
+    "Reset Rate",
+    "Time Save",
+];
 
 lazy_static! {
-    static ref HOURS: Regex = Regex::new(r"^(\d+):([0-5]\d):([0-5]\d)(?:\.(\d{1,3}))?$").unwrap();
-    static ref MINS: Regex = Regex::new(r"^([0-5]?\d):([0-5]\d)(?:\.(\d{1,3}))?$").unwrap();
-    static ref SECS: Regex = Regex::new(r"^([0-5]?\d)(?:\.(\d{1,3}))?$").unwrap();
     static ref RUN: Mutex<Run> = Mutex::new(Run::empty());
-    static ref VECS: Mutex<(Vec<u128>, Vec<u128>, Vec<String>)> =
+    // every category in the open file, so that saving keeps the ones not being edited
+    static ref CATEGORIES: Mutex<Categories> = Mutex::new(Run::empty().into());
+    // where saving writes to, empty until the user picks a file
+    static ref SAVE_PATH: Mutex<String> = Mutex::new(String::new());
+    static ref VECS: Mutex<(Vec<TimeType>, Vec<TimeType>, Vec<String>)> =
         Mutex::new((vec![], vec![], vec![]));
 }
 
@@ -35,10 +53,14 @@ fn open_split_file() -> Option<String> {
 }
 
 fn get_save_as() -> Option<String> {
-    match tfd::save_file_dialog_with_filter("Save to MSF file", "", &["*.msf"], "mist split files")
-    {
+    match tfd::save_file_dialog_with_filter(
+        "Save to MSF or LSS file",
+        "",
+        &["*.msf", "*.lss"],
+        "split files",
+    ) {
         Some(mut p) => {
-            if p.ends_with(".msf") {
+            if p.ends_with(".msf") || p.ends_with(".lss") {
                 Some(p)
             } else {
                 p.push_str(".msf");
@@ -49,75 +71,156 @@ fn get_save_as() -> Option<String> {
     }
 }
 
-fn str_to_ms(tm: String) -> u128 {
-    let mut ms: u128 = 0;
-    if HOURS.is_match(&tm) {
-        let caps = HOURS.captures_iter(&tm).next().unwrap();
-        ms = (caps[1].parse::<u128>().unwrap() * 3600000)
-            + (caps[2].parse::<u128>().unwrap() * 60000)
-            + (caps[3].parse::<u128>().unwrap() * 1000)
-            + caps
-                .get(4)
-                .map_or("0", |m| m.as_str())
-                .parse::<u128>()
-                .unwrap();
-    } else if MINS.is_match(&tm) {
-        let caps = MINS.captures_iter(&tm).next().unwrap();
-        ms = (caps[1].parse::<u128>().unwrap() * 60000)
-            + (caps[2].parse::<u128>().unwrap() * 1000)
-            + caps
-                .get(3)
-                .map_or("0", |m| m.as_str())
-                .parse::<u128>()
-                .unwrap();
-    } else if SECS.is_match(&tm) {
-        let caps = SECS.captures_iter(&tm).next().unwrap();
-        ms = (caps[1].parse::<u128>().unwrap() * 1000)
-            + caps
-                .get(2)
-                .map_or("0", |m| m.as_str())
-                .parse::<u128>()
-                .unwrap();
+// read every category from an msf file, or the one run in an lss file
+fn open_categories(path: &str) -> Result<Categories, ParseError> {
+    if path.ends_with(".lss") {
+        LssParser::new(path.to_owned())
+            .parse()
+            .map(Categories::from)
+    } else {
+        MsfParser::new(path).parse_categories()
+    }
+}
+
+// start editing the selected category of `cats`
+fn set_categories(cats: Categories) {
+    let run = cats.selected_run().clone();
+    *VECS.lock().unwrap() = (
+        run.pb_times().to_owned(),
+        run.gold_times().to_owned(),
+        run.splits().to_owned(),
+    );
+    *RUN.lock().unwrap() = run;
+    *CATEGORIES.lock().unwrap() = cats;
+    SAVE_PATH.lock().unwrap().clear();
+}
+
+// write `run` in place of the selected category, along with all the others, or as the only run
+// if it is saved to a LiveSplit file
+fn save_run(run: &Run, path: &str) -> Result<(), ParseError> {
+    if path.ends_with(".lss") {
+        return LssParser::new(path.to_owned()).write(run);
+    }
+    let mut cats = CATEGORIES.lock().unwrap();
+    let selected = cats.selected();
+    cats.set_run(selected, run.clone());
+    MsfParser::new(path).write_categories(&cats)
+}
+
+// text for a time in the table, empty if there is none
+fn time_text(time: TimeType) -> String {
+    if time.is_none() {
+        String::new()
+    } else {
+        ns_to_readable(time.raw(), None)
+    }
+}
+
+// time typed into a table cell, with no time if it is empty or not a time
+fn str_to_ns(tm: String) -> TimeType {
+    TimeType::from_time(readable_to_ns(&tm).unwrap_or(0))
+}
+
+fn basis_of(choice: &menu::Choice) -> TargetBasis {
+    if choice.value() == 1 {
+        TargetBasis::PersonalBest
+    } else {
+        TargetBasis::Golds
+    }
+}
+
+// show the target of the run being edited in its inputs
+fn show_target(inp: &mut input::Input, choice: &mut menu::Choice) {
+    let target = RUN.lock().unwrap().target();
+    inp.set_value(&target.map_or_else(String::new, |t| ns_to_readable(t.time(), None)));
+    choice.set_value(match target.map(|t| t.basis()) {
+        Some(TargetBasis::PersonalBest) => 1,
+        _ => 0,
+    });
+}
+
+// show the countdown time limit of the run being edited, empty if it counts up
+fn show_limit(inp: &mut input::Input) {
+    let limit = RUN.lock().unwrap().countdown();
+    inp.set_value(&limit.map_or_else(String::new, |t| ns_to_readable(t, None)));
+}
+
+// open a window with how consistent each segment has been over the run's attempts
+fn show_stats() {
+    let run = RUN.lock().unwrap();
+    let text = |t: Option<f64>| t.map_or_else(|| "-".into(), |t| ns_split_time_text(t as u128));
+    let rows = run
+        .splits()
+        .iter()
+        .zip(segment_stats(&run, TimingMethod::RealTime))
+        .map(|(name, s)| {
+            [
+                name.clone(),
+                text(s.mean),
+                text(s.median),
+                text(s.std_dev),
+                text(s.percentile(0.1)),
+                text(s.percentile(0.9)),
+                if s.reached == 0 {
+                    "-".into()
+                } else {
+                    format!("{:.0}%", s.reset_rate() * 100.0)
+                },
+                s.time_save.map_or_else(|| "-".into(), ns_split_time_text),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let mut win = Window::default()
+        .with_size(745, 400)
+        .center_screen()
+        .with_label("segment statistics");
+    let mut table = table::Table::new(5, 5, 735, 390, "");
+    table.set_rows(rows.len().try_into().unwrap());
+    table.set_cols(8);
+    table.set_col_header(true);
+    table.set_col_width(0, 160);
+    for col in 1..8 {
+        table.set_col_width(col, 80);
     }
-    return ms;
+    table.end();
+    win.end();
+    win.show();
+    table.draw_cell(move |_, ctx, row, col, x, y, w, h| {
+        let (text, frame, color) = match ctx {
+            table::TableContext::StartPage => return draw::set_font(Font::Helvetica, 14),
+            table::TableContext::ColHeader => (
+                STATS_HEADERS[col as usize],
+                FrameType::ThinUpBox,
+                Color::FrameDefault,
+            ),
+            table::TableContext::Cell => (
+                rows[row as usize][col as usize].as_str(),
+                FrameType::FlatBox,
+                Color::White,
+            ),
+            _ => return,
+        };
+        draw::push_clip(x, y, w, h);
+        draw::draw_box(frame, x, y, w, h, color);
+        draw::set_draw_color(Color::Black);
+        draw::draw_text2(text, x, y, w, h, Align::Center);
+        draw::pop_clip();
+    });
 }
 
 fn main() {
-    let path = open_split_file();
-    let mut save_path: String = "".to_string();
-    match path {
-        Some(ref p) => {
-            let run: Run;
-            if p.ends_with(".msf") {
-                let parser = MsfParser::new();
-                let f = std::fs::File::open(p).unwrap();
-                run = parser.parse(std::io::BufReader::new(f)).unwrap();
-                *VECS.lock().unwrap() = (
-                    run.pb_times().to_owned(),
-                    run.gold_times().to_owned(),
-                    run.splits().to_owned(),
-                );
-                *RUN.lock().unwrap() = run;
-            } else {
-                let f = std::fs::File::open(p).unwrap();
-                let mut parser = LssParser::new(std::io::BufReader::new(f));
-                run = parser.parse();
-                *VECS.lock().unwrap() = (
-                    run.pb_times().to_owned(),
-                    run.gold_times().to_owned(),
-                    run.splits().to_owned(),
-                );
-                *RUN.lock().unwrap() = run;
-            }
+    let app = app::App::default();
+    if let Some(p) = open_split_file() {
+        match open_categories(&p) {
+            Ok(c) => set_categories(c),
+            Err(e) => dialog::alert_default(&e.to_string()),
         }
-        None => *RUN.lock().unwrap() = Run::empty(),
     }
-    let app = app::App::default();
     let mut win = Window::default()
-        .with_size(510, 635)
+        .with_size(510, 660)
         .center_screen()
         .with_label("mist split editor");
-    let mut table = table::Table::new(5, 85, 503, 550, "");
+    let mut table = table::Table::new(5, 110, 503, 545, "");
     let og_len: i32 = RUN.lock().unwrap().splits().len().try_into().unwrap();
     let og_len = if og_len == 0 { 1 } else { og_len };
     table.set_rows(og_len);
@@ -132,8 +235,13 @@ fn main() {
     let mut add_button = button::Button::new(342, 60, 80, 25, "add split");
     let mut sub_button = button::Button::new(261, 60, 80, 25, "remove split");
     let mut open_button = button::Button::new(180, 60, 80, 25, "open file");
+    let mut stats_button = button::Button::new(423, 85, 80, 25, "statistics");
     let mut title_inp = input::Input::new(100, 5, 180, 25, "Category Title: ");
     let mut cat_inp = input::Input::new(100, 30, 180, 25, "Game Title: ");
+    let mut target_inp = input::Input::new(370, 5, 133, 25, "Target Time: ");
+    let mut basis_choice = menu::Choice::new(370, 30, 133, 25, "Spread Over: ");
+    let mut limit_inp = input::Input::new(80, 60, 95, 25, "Time Limit: ");
+    basis_choice.add_choice("golds|personal best");
     win.make_resizable(false);
     win.end();
     win.show();
@@ -141,37 +249,62 @@ fn main() {
     title_inp.set_callback(|inp| RUN.lock().unwrap().set_game_title(inp.value()));
     cat_inp.set_value(RUN.lock().unwrap().category());
     title_inp.set_value(RUN.lock().unwrap().game_title());
+    show_target(&mut target_inp, &mut basis_choice);
+    show_limit(&mut limit_inp);
+    limit_inp.set_callback(|inp| {
+        let text = inp.value();
+        let limit = if text.trim().is_empty() {
+            None
+        } else if let Some(t) = readable_to_ns(&text) {
+            Some(t).filter(|&t| t != 0)
+        } else {
+            dialog::alert_default("invalid time limit entered");
+            return;
+        };
+        RUN.lock().unwrap().set_countdown(limit);
+    });
+    let choice = basis_choice.clone();
+    target_inp.set_callback(move |inp| {
+        let text = inp.value();
+        let target = if text.trim().is_empty() {
+            None
+        } else if let Some(t) = readable_to_ns(&text) {
+            Some(Target::new(t, basis_of(&choice)))
+        } else {
+            dialog::alert_default("invalid target time entered");
+            return;
+        };
+        RUN.lock().unwrap().set_target(target);
+    });
+    basis_choice.set_callback(|choice| {
+        let mut run = RUN.lock().unwrap();
+        if let Some(t) = run.target() {
+            run.set_target(Some(Target::new(t.time(), basis_of(choice))));
+        }
+    });
+    stats_button.set_callback(|_| show_stats());
     let mut tbl = table.clone();
+    let (mut cat, mut title) = (cat_inp.clone(), title_inp.clone());
+    let (mut target, mut basis) = (target_inp.clone(), basis_choice.clone());
+    let mut limit = limit_inp.clone();
     open_button.set_callback(move |_| {
-        let path = open_split_file();
-        match path {
-            Some(ref p) => {
-                if p.ends_with(".msf") {
-                    let parser = MsfParser::new();
-                    let f = std::fs::File::open(p).unwrap();
-                    let run = parser.parse(std::io::BufReader::new(f)).unwrap();
-                    *VECS.lock().unwrap() = (
-                        run.pb_times().to_owned(),
-                        run.gold_times().to_owned(),
-                        run.splits().to_owned(),
-                    );
-                    *RUN.lock().unwrap() = run;
-                } else {
-                    let f = std::fs::File::open(p).unwrap();
-                    let mut parser = LssParser::new(std::io::BufReader::new(f));
-                    let run = parser.parse();
-                    *VECS.lock().unwrap() = (
-                        run.pb_times().to_owned(),
-                        run.gold_times().to_owned(),
-                        run.splits().to_owned(),
-                    );
-                    *RUN.lock().unwrap() = run;
-                }
+        let cats = match open_split_file().map(|p| open_categories(&p)) {
+            Some(Ok(c)) => c,
+            Some(Err(e)) => {
+                dialog::alert_default(&e.to_string());
+                return;
             }
             None => return,
-        }
+        };
+        set_categories(cats);
+        show_target(&mut target, &mut basis);
+        show_limit(&mut limit);
+        let run = RUN.lock().unwrap();
+        cat.set_value(run.category());
+        title.set_value(run.game_title());
+        let len: i32 = run.splits().len().try_into().unwrap();
         TableExt::clear(&mut tbl);
-        tbl.set_rows(og_len);
+        tbl.set_rows(if len == 0 { 1 } else { len });
         tbl.set_row_header(true);
         tbl.set_cols(3);
         tbl.set_col_header(true);
@@ -185,29 +318,19 @@ fn main() {
         run.set_pb_times(&vecs.0);
         run.set_gold_times(&vecs.1);
         run.set_splits(&vecs.2);
-        run.set_pb(vecs.1.iter().sum());
-        // fill sum times with empty ones until i figure out how i want to handle it
-        run.set_sum_times(&vecs.2.iter().map(|_| (0u128, 0u128)).collect());
+        run.set_pb(TimeType::from_time(vecs.0.iter().map(|t| t.raw()).sum()));
         unsafe {
             if !ILLEGAL {
-                let parser = MsfParser::new();
-                let f: std::fs::File;
-                if save_path != "".to_string() {
-                    f = std::fs::File::create(save_path.clone()).unwrap();
-                    parser.write(&run, f).unwrap();
-                } else {
-                    let name = get_save_as();
-                    match name {
-                        Some(ref p) => {
-                            if p != "()" {
-                                f = std::fs::File::create(p).unwrap();
-                                parser.write(&run, f).unwrap();
-                                save_path = p.to_owned();
-                            }
-                        }
-                        None => {}
+                let mut save_path = SAVE_PATH.lock().unwrap();
+                if save_path.is_empty() {
+                    match get_save_as() {
+                        Some(p) if p != "()" => *save_path = p,
+                        _ => return,
                     }
                 }
+                if let Err(e) = save_run(&run, &save_path) {
+                    dialog::alert_default(&e.to_string());
+                }
             } else {
                 dialog::alert_default("invalid time(s) entered");
             }
@@ -243,24 +366,24 @@ fn main() {
                     })
                 } else if col == 1 {
                     if (row as usize) < VECS.lock().unwrap().0.len() {
-                        inp.set_value(&ms_to_readable(VECS.lock().unwrap().0[row as usize], None));
+                        inp.set_value(&time_text(VECS.lock().unwrap().0[row as usize]));
                     }
                     inp.set_callback(move |input| {
                         if (row as usize) >= VECS.lock().unwrap().0.len() {
-                            VECS.lock().unwrap().0.push(str_to_ms(input.value()))
+                            VECS.lock().unwrap().0.push(str_to_ns(input.value()))
                         } else {
-                            VECS.lock().unwrap().0[row as usize] = str_to_ms(input.value());
+                            VECS.lock().unwrap().0[row as usize] = str_to_ns(input.value());
                         }
                     })
                 } else if col == 2 {
                     if (row as usize) < VECS.lock().unwrap().1.len() {
-                        inp.set_value(&ms_to_readable(VECS.lock().unwrap().1[row as usize], None));
+                        inp.set_value(&time_text(VECS.lock().unwrap().1[row as usize]));
                     }
                     inp.set_callback(move |input| {
                         if (row as usize) >= VECS.lock().unwrap().1.len() {
-                            VECS.lock().unwrap().1.push(str_to_ms(input.value()))
+                            VECS.lock().unwrap().1.push(str_to_ns(input.value()))
                         } else {
-                            VECS.lock().unwrap().1[row as usize] = str_to_ms(input.value());
+                            VECS.lock().unwrap().1[row as usize] = str_to_ns(input.value());
                         }
                     })
                 }
@@ -276,23 +399,23 @@ fn main() {
                 } else if col == 1 {
                     inp.set_callback(move |input| {
                         if (row as usize) > VECS.lock().unwrap().0.len() {
-                            VECS.lock().unwrap().0.push(str_to_ms(input.value()))
+                            VECS.lock().unwrap().0.push(str_to_ns(input.value()))
                         } else {
                             VECS.lock()
                                 .unwrap()
                                 .0
-                                .insert(row as usize, str_to_ms(input.value()));
+                                .insert(row as usize, str_to_ns(input.value()));
                         }
                     })
                 } else if col == 2 {
                     inp.set_callback(move |input| {
                         if (row as usize) > VECS.lock().unwrap().1.len() {
-                            VECS.lock().unwrap().1.push(str_to_ms(input.value()))
+                            VECS.lock().unwrap().1.push(str_to_ns(input.value()))
                         } else {
                             VECS.lock()
                                 .unwrap()
                                 .1
-                                .insert(row as usize, str_to_ms(input.value()));
+                                .insert(row as usize, str_to_ns(input.value()));
                         }
                     })
                 }
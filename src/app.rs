@@ -1,13 +1,16 @@
 use crate::keybinds::Keybinds;
 use crate::render::RenderState;
+#[cfg(feature = "lss")]
+use mist_core::parse::LssParser;
 use mist_core::{
-    config::Config,
+    config::{self, Config, ResetPolicy},
     dialogs,
+    error::ParseError,
     parse::MsfParser,
     timer::{
         dump::StateDump,
-        state::{RunState, RunUpdate, StateChangeRequest},
-        Run,
+        state::{RunState, RunUpdate, StateChange, StateChangeRequest},
+        Categories, CustomComparison, Marathon, Run,
     },
 };
 use sdl2::{
@@ -20,6 +23,8 @@ use sdl2::{
 use sdl2::{image::ImageRWops, rwops::RWops};
 use std::{
     cell::RefCell,
+    io::ErrorKind,
+    path::PathBuf,
     rc::Rc,
     thread,
     time::{Duration, Instant},
@@ -28,14 +33,29 @@ use std::{
 pub struct App<'a, 'b> {
     _context: sdl2::Sdl,
     run: Rc<RefCell<Run>>,
+    categories: Categories,
+    categories_changed: bool,
     ren_state: RenderState<'a, 'b>,
     run_state: RunState,
     config: Config,
     ev_pump: sdl2::EventPump,
     msf: MsfParser,
+    recovery: Option<PathBuf>,
+    recovery_saved: bool,
+    // split picked to start practicing from, while picking one
+    practice_select: Option<usize>,
+    marathon: Option<Marathon>,
+    // when the first run of the marathon was started and when the last one finished
+    marathon_start: Option<Instant>,
+    marathon_end: Option<Instant>,
+    // LiveSplit file the run was last saved to, so it can be saved there again without asking
+    #[cfg(feature = "lss")]
+    lss_path: Option<String>,
 }
 
 static ONE_SIXTIETH: Duration = Duration::new(0, 1_000_000_000 / 60);
+// how long a finished marathon run can still be unsplit before it is saved and the next one loaded
+static MARATHON_GRACE: Duration = Duration::new(5, 0);
 
 impl<'a, 'b> App<'a, 'b> {
     pub fn init(
@@ -44,7 +64,7 @@ impl<'a, 'b> App<'a, 'b> {
         s_font: &'a Font<'b, 'a>,
     ) -> Result<Self, String> {
         let video = context.video()?;
-        let mut config = Config::open()?;
+        let mut config = Config::open().map_err(|e| e.to_string())?;
         let mut window = video
             .window("mist", config.win_size().0, config.win_size().1)
             .position_centered()
@@ -67,19 +87,21 @@ impl<'a, 'b> App<'a, 'b> {
                 None => String::new(),
             }
         };
-        let (run, msf) = loop {
+        let (categories, msf) = loop {
             if path.is_empty() {
-                break (Run::empty(), MsfParser::new(""));
+                break (Run::empty().into(), MsfParser::new(""));
             } else {
                 let msf = MsfParser::new(&path);
-                match msf.parse() {
-                    Ok(r) => {
+                match msf.parse_categories() {
+                    Ok(c) => {
                         config.set_file(&path);
-                        break (r, msf);
+                        break (c, msf);
                     }
-                    Err(_) => {
-                        if !dialogs::try_again() {
-                            break (Run::empty(), msf);
+                    // the file from the config was moved or deleted, so just ask for another one
+                    Err(ParseError::Io(ref e)) if e.kind() == ErrorKind::NotFound => {}
+                    Err(e) => {
+                        if !dialogs::try_again(&e) {
+                            break (Run::empty().into(), msf);
                         }
                     }
                 }
@@ -89,7 +111,7 @@ impl<'a, 'b> App<'a, 'b> {
                 None => String::new(),
             }
         };
-        let run = Rc::new(RefCell::new(run));
+        let run = Rc::new(RefCell::new(categories.selected_run().clone()));
 
         canvas
             .window_mut()
@@ -99,7 +121,12 @@ impl<'a, 'b> App<'a, 'b> {
                 run.borrow().category(),
             ))
             .map_err(|_| get_error())?;
-        let app = App {
+        let recovery = if config.autosave_interval() == 0 {
+            None
+        } else {
+            config::recovery_path().ok()
+        };
+        let mut app = App {
             _context: context,
             ren_state: RenderState::new(Rc::clone(&run), canvas, &config, t_font, s_font)?,
             run_state: RunState::new(Rc::clone(&run)),
@@ -107,11 +134,103 @@ impl<'a, 'b> App<'a, 'b> {
             ev_pump,
             msf,
             run,
+            categories,
+            categories_changed: false,
+            recovery_saved: matches!(recovery, Some(ref p) if p.exists()),
+            recovery,
+            practice_select: None,
+            marathon: None,
+            marathon_start: None,
+            marathon_end: None,
+            #[cfg(feature = "lss")]
+            lss_path: None,
         };
+        app.recover()?;
 
         Ok(app)
     }
 
+    // offer to resume a run that was still going when mist last closed, switching to the split
+    // file and category it came from first
+    fn recover(&mut self) -> Result<(), String> {
+        if !self.recovery_saved {
+            return Ok(());
+        }
+        let d = match self.recovery.as_ref().map(StateDump::open) {
+            Some(Ok(d)) => d,
+            _ => return Ok(()),
+        };
+        if !dialogs::resume_check() {
+            return Ok(());
+        }
+        if d.version < 2 {
+            dialogs::resume_failed("the recovery file does not say which split file it is from");
+            return Ok(());
+        }
+        if d.file != self.msf.filename() || d.category != self.categories.selected() {
+            let mut categories = if d.file.is_empty() {
+                Run::empty().into()
+            } else {
+                match MsfParser::new(&d.file).parse_categories() {
+                    Ok(c) => c,
+                    Err(e) => {
+                        dialogs::resume_failed(&format!("{} could not be opened: {}", d.file, e));
+                        return Ok(());
+                    }
+                }
+            };
+            if d.category >= categories.len() {
+                dialogs::resume_failed(&format!("{} no longer has its category", d.file));
+                return Ok(());
+            }
+            categories.set_selected(d.category);
+            self.msf.set_filename(&d.file);
+            if !d.file.is_empty() {
+                self.config.set_file(&d.file);
+            }
+            self.run.replace(categories.selected_run().clone());
+            self.categories = categories;
+            self.categories_changed = false;
+            self.run_state = RunState::new(Rc::clone(&self.run));
+            self.ren_state.reload_run()?;
+        }
+        if self.run.borrow().category() != d.run.category()
+            || self.run.borrow().splits() != d.run.splits()
+        {
+            dialogs::resume_failed("its category in the split file has changed since");
+            return Ok(());
+        }
+        self.run_state.read_dump(&d);
+        self.ren_state.read_dump(&d)?;
+        Ok(())
+    }
+
+    // keep the recovery file up to date while there is a run in progress, and remove it after
+    fn autosave(&mut self, split: bool, last_save: &mut Instant) {
+        let path = match self.recovery {
+            Some(ref p) => p,
+            None => return,
+        };
+        if self.run_state.in_attempt() {
+            if split || last_save.elapsed().as_secs() >= self.config.autosave_interval() {
+                let d = create_dump(
+                    &self.run_state,
+                    &self.ren_state,
+                    &self.msf,
+                    &self.categories,
+                );
+                // not being able to save shouldn't interrupt the run
+                if d.write(path).is_ok() {
+                    self.recovery_saved = true;
+                }
+                *last_save = Instant::now();
+            }
+        } else if self.recovery_saved {
+            let _ = std::fs::remove_file(path);
+            self.recovery_saved = false;
+        }
+    }
+
     pub fn run(mut self, t_font: &'a Font<'b, 'a>, s_font: &'a Font<'b, 'a>) -> Result<(), String> {
         let no_file = self.config.file().is_none();
 
@@ -120,6 +239,11 @@ impl<'a, 'b> App<'a, 'b> {
         let mut binds = Keybinds::from_raw(self.config.binds())?;
         let mut state_change_queue = vec![];
         let mut update: RunUpdate;
+        let mut last_save = Instant::now();
+        // when the marathon run that is waiting to be saved finished
+        let mut marathon_finish: Option<Instant> = None;
+        // whether the user asked to start the next marathon run before the grace period is over
+        let mut marathon_advance = false;
 
         // main loop
         'running: loop {
@@ -132,6 +256,30 @@ impl<'a, 'b> App<'a, 'b> {
                 println!("{:?}", event);
 
                 match event {
+                    // while picking a split to practice, the arrow keys move the selection and
+                    // start/split begins practice
+                    Event::KeyDown {
+                        keycode: Some(k), ..
+                    } if self.practice_select.is_some() => {
+                        let selected = self.practice_select.unwrap();
+                        let last = self.run.borrow().splits().len().saturating_sub(1);
+                        self.practice_select = if k == Keycode::Up {
+                            Some(selected.saturating_sub(1))
+                        } else if k == Keycode::Down {
+                            Some((selected + 1).min(last))
+                        } else if k == binds.start_split {
+                            state_change_queue.push(StateChangeRequest::Practice {
+                                split: selected,
+                                update_golds: self.config.practice_golds(),
+                            });
+                            None
+                        } else if k == Keycode::Escape || k == binds.practice {
+                            None
+                        } else {
+                            Some(selected)
+                        };
+                        self.ren_state.select_split(self.practice_select)?;
+                    }
                     // quit program on esc or being told by wm to close
                     Event::Quit { .. }
                     | Event::KeyDown {
@@ -143,12 +291,23 @@ impl<'a, 'b> App<'a, 'b> {
                         self.ren_state.scroll(y);
                     }
 
+                    #[cfg(feature = "lss")]
+                    Event::KeyDown {
+                        keycode: Some(k),
+                        repeat: false,
+                        ..
+                    } if k == binds.save_lss && !self.run_state.is_running() => {
+                        save_lss(&mut self.lss_path, &self.run.borrow())?;
+                    }
+
                     Event::KeyDown {
                         keycode: Some(k),
                         repeat: false,
                         ..
                     } => {
-                        if k == binds.start_split {
+                        if k == binds.start_split && marathon_finish.is_some() {
+                            marathon_advance = true;
+                        } else if k == binds.start_split {
                             state_change_queue.push(StateChangeRequest::Split);
                         } else if k == binds.pause {
                             state_change_queue.push(StateChangeRequest::Pause);
@@ -162,31 +321,36 @@ impl<'a, 'b> App<'a, 'b> {
                             state_change_queue.push(StateChangeRequest::Unsplit);
                         } else if k == binds.skip_split {
                             state_change_queue.push(StateChangeRequest::Skip);
+                        } else if k == binds.pause_game_time {
+                            state_change_queue.push(StateChangeRequest::PauseGameTime);
+                        } else if k == binds.switch_method {
+                            state_change_queue.push(StateChangeRequest::SwitchTimingMethod);
+                        } else if k == binds.undo_reset {
+                            state_change_queue.push(StateChangeRequest::UndoReset);
                         } else if !self.run_state.is_running() {
                             if k == binds.load_splits {
                                 // save the previous run if it was updated
-                                if (self.run_state.needs_save() || no_file) && dialogs::save_check()
-                                {
-                                    if self.msf.no_path() {
-                                        if let Some(s) = dialogs::get_save_as() {
-                                            self.msf.set_filename(&s);
-                                            self.msf.write(&self.run.borrow())?;
-                                        }
-                                    } else {
-                                        self.msf.write(&self.run.borrow())?;
-                                    }
-                                }
+                                save_if_changed(
+                                    &mut self.msf,
+                                    &mut self.categories,
+                                    &self.run.borrow(),
+                                    self.run_state.needs_save()
+                                        || self.categories_changed
+                                        || no_file,
+                                )?;
                                 // open a file dialog to get a new split file + run
                                 // if the user cancelled, do nothing
                                 while let Some(x) = dialogs::get_run_path() {
                                     self.msf.set_filename(&x);
-                                    match self.msf.parse() {
-                                        Ok(r) => {
-                                            self.run.replace(r);
+                                    match self.msf.parse_categories() {
+                                        Ok(c) => {
+                                            replace_run(&self.run, c.selected_run());
+                                            self.categories = c;
+                                            self.categories_changed = false;
                                             break;
                                         }
-                                        Err(_) => {
-                                            if !dialogs::try_again() {
+                                        Err(e) => {
+                                            if !dialogs::try_again(&e) {
                                                 break;
                                             }
                                         }
@@ -195,8 +359,94 @@ impl<'a, 'b> App<'a, 'b> {
                                 self.config.set_file(self.msf.filename());
                                 self.run_state = RunState::new(Rc::clone(&self.run));
                                 self.ren_state.reload_run()?;
+                                #[cfg(feature = "lss")]
+                                {
+                                    self.lss_path = None;
+                                }
+                                self.marathon = None;
+                                self.marathon_start = None;
+                                self.marathon_end = None;
+                                marathon_finish = None;
+                                self.ren_state.set_marathon(None)?;
+                            } else if k == binds.load_marathon {
+                                save_if_changed(
+                                    &mut self.msf,
+                                    &mut self.categories,
+                                    &self.run.borrow(),
+                                    self.run_state.needs_save()
+                                        || self.categories_changed
+                                        || no_file,
+                                )?;
+                                while let Some(p) = dialogs::get_marathon_path() {
+                                    let marathon = match Marathon::open(&p) {
+                                        Ok(m) => m,
+                                        Err(e) => {
+                                            if !dialogs::try_again(&e) {
+                                                break;
+                                            }
+                                            continue;
+                                        }
+                                    };
+                                    let first =
+                                        marathon.current_file().map(|f| f.display().to_string());
+                                    if let Some(c) = load_categories(&mut self.msf, first) {
+                                        replace_run(&self.run, c.selected_run());
+                                        self.categories = c;
+                                        self.categories_changed = false;
+                                        self.config.set_file(self.msf.filename());
+                                        self.run_state = RunState::new(Rc::clone(&self.run));
+                                        self.ren_state.reload_run()?;
+                                        self.ren_state.set_marathon(Some(0))?;
+                                        self.marathon = Some(marathon);
+                                        #[cfg(feature = "lss")]
+                                        {
+                                            self.lss_path = None;
+                                        }
+                                        self.marathon_start = None;
+                                        self.marathon_end = None;
+                                        marathon_finish = None;
+                                    }
+                                    break;
+                                }
+                            } else if k == binds.switch_category && self.categories.len() > 1 {
+                                // keep any changes to the current category before switching away
+                                let current = self.categories.selected();
+                                self.categories.set_run(current, self.run.borrow().clone());
+                                self.categories
+                                    .set_selected((current + 1) % self.categories.len());
+                                replace_run(&self.run, self.categories.selected_run());
+                                self.categories_changed = true;
+                                self.run_state = RunState::new(Rc::clone(&self.run));
+                                self.ren_state.reload_run()?;
+                                #[cfg(feature = "lss")]
+                                {
+                                    self.lss_path = None;
+                                }
+                            } else if k == binds.practice
+                                && !self.run_state.in_attempt()
+                                && !self.run_state.is_practicing()
+                                && !self.run.borrow().splits().is_empty()
+                            {
+                                self.practice_select = Some(0);
+                                self.ren_state.select_split(self.practice_select)?;
+                            } else if k == binds.load_ghost {
+                                while let Some(p) = dialogs::get_ghost_path() {
+                                    match parse_ghost(&p) {
+                                        Ok(ghost) => {
+                                            self.run_state
+                                                .set_ghost(Some(CustomComparison::from_pb(&ghost)));
+                                            self.ren_state.reload_ghost()?;
+                                            break;
+                                        }
+                                        Err(e) => {
+                                            if !dialogs::try_again(&e) {
+                                                break;
+                                            }
+                                        }
+                                    }
+                                }
                             } else if k == binds.load_config {
-                                match dialogs::open_config() {
+                                match dialogs::open_config().map_err(|e| e.to_string()) {
                                     Ok(c) => {
                                         if let Some(conf) = c {
                                             self.config = conf;
@@ -206,15 +456,24 @@ impl<'a, 'b> App<'a, 'b> {
                                                 s_font,
                                             )?;
                                             binds = Keybinds::from_raw(self.config.binds())?;
+                                            self.ren_state.set_marathon(marathon_time(
+                                                self.marathon.is_some(),
+                                                self.marathon_start,
+                                                self.marathon_end,
+                                            ))?;
                                         }
                                     }
                                     Err(e) => return Err(e),
                                 }
                             } else if k == binds.dump_state {
                                 if let Some(p) = dialogs::get_dump_save() {
-                                    let mut d = self.run_state.create_state_dump();
-                                    self.ren_state.fill_dump(&mut d);
-                                    d.write(p)?;
+                                    let d = create_dump(
+                                        &self.run_state,
+                                        &self.ren_state,
+                                        &self.msf,
+                                        &self.categories,
+                                    );
+                                    d.write(p).map_err(|e| e.to_string())?;
                                 }
                             } else if k == binds.load_state {
                                 while let Some(p) = dialogs::get_dump_path() {
@@ -224,8 +483,8 @@ impl<'a, 'b> App<'a, 'b> {
                                             self.ren_state.read_dump(&d)?;
                                             break;
                                         }
-                                        Err(_) => {
-                                            if !dialogs::try_again() {
+                                        Err(e) => {
+                                            if !dialogs::try_again(&e) {
                                                 break;
                                             }
                                         }
@@ -244,10 +503,107 @@ impl<'a, 'b> App<'a, 'b> {
                     _ => {}
                 }
             }
+            let practicing = self.run_state.is_practicing();
             update = self.run_state.update(&state_change_queue[..]);
             state_change_queue.clear();
+            if self.run_state.has_reset_golds()
+                && update
+                    .change
+                    .iter()
+                    .any(|c| matches!(c, StateChange::Reset { .. }))
+            {
+                let keep = match self.config.reset_policy() {
+                    ResetPolicy::Keep => true,
+                    ResetPolicy::Ask => dialogs::keep_golds_check(),
+                    ResetPolicy::Discard => false,
+                };
+                if keep {
+                    state_change_queue.push(StateChangeRequest::KeepResetGolds);
+                }
+            }
+            if let (Some(ref marathon), false) =
+                (&self.marathon, practicing || self.run_state.is_practicing())
+            {
+                for change in &update.change {
+                    match change {
+                        // the marathon clock starts with its first run
+                        StateChange::EnterOffset | StateChange::EnterSplit { idx: 0 }
+                            if self.marathon_start.is_none() =>
+                        {
+                            self.marathon_start = Some(Instant::now());
+                        }
+                        // resetting the first run starts the marathon over
+                        StateChange::Reset { .. }
+                            if marathon.current() == 0 && marathon_finish.is_none() =>
+                        {
+                            self.marathon_start = None;
+                        }
+                        StateChange::Finish => {
+                            marathon_finish = Some(Instant::now());
+                            if marathon.is_last() {
+                                self.marathon_end = marathon_finish;
+                            }
+                        }
+                        StateChange::UndoFinish => {
+                            marathon_finish = None;
+                            self.marathon_end = None;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            let split = update.change.iter().any(|c| {
+                matches!(
+                    c,
+                    StateChange::ExitSplit { .. } | StateChange::EnterSplit { .. }
+                )
+            });
             self.ren_state.update(update)?;
+            self.autosave(split, &mut last_save);
             self.ren_state.render()?;
+            // once the finished run of a marathon can no longer be unsplit, save it to its own
+            // file and move on to the next one, starting it if that is what the user asked for
+            let advance = std::mem::take(&mut marathon_advance);
+            if let (Some(at), Some(marathon)) = (marathon_finish, self.marathon.as_mut()) {
+                if advance || at.elapsed() >= MARATHON_GRACE {
+                    marathon_finish = None;
+                    write_splits(&mut self.msf, &mut self.categories, &self.run.borrow())?;
+                    let next = marathon.finish_run().map(|f| f.display().to_string());
+                    if next.is_none() {
+                        self.marathon = None;
+                    } else if let Some(c) = load_categories(&mut self.msf, next) {
+                        replace_run(&self.run, c.selected_run());
+                        self.categories = c;
+                        self.categories_changed = false;
+                        self.config.set_file(self.msf.filename());
+                        self.run_state = RunState::new(Rc::clone(&self.run));
+                        self.ren_state.reload_run()?;
+                        #[cfg(feature = "lss")]
+                        {
+                            self.lss_path = None;
+                        }
+                        if advance {
+                            state_change_queue.push(StateChangeRequest::Split);
+                        }
+                    } else {
+                        self.marathon = None;
+                        self.marathon_start = None;
+                        self.marathon_end = None;
+                    }
+                    self.ren_state.set_marathon(marathon_time(
+                        self.marathon.is_some(),
+                        self.marathon_start,
+                        self.marathon_end,
+                    ))?;
+                }
+            }
+            if self.marathon.is_some() {
+                self.ren_state.set_marathon(marathon_time(
+                    true,
+                    self.marathon_start,
+                    self.marathon_end,
+                ))?;
+            }
             if Instant::now().duration_since(frame_time) <= ONE_SIXTIETH {
                 thread::sleep(
                     // if the entire loop pass was completed in under 1/60 second, delay to keep the framerate at ~60fps
@@ -256,19 +612,115 @@ impl<'a, 'b> App<'a, 'b> {
             }
         }
         self.config.set_win_size(self.ren_state.win_size());
-        self.config.save()?;
+        self.config.save().map_err(|e| e.to_string())?;
         // if splits were updated, prompt user to save the split file
-        if (self.run_state.needs_save() || no_file) && dialogs::save_check() {
-            if self.msf.no_path() {
-                let p = dialogs::get_save_as();
-                if let Some(s) = p {
-                    self.msf.set_filename(s);
-                    self.msf.write(&self.run.borrow())?;
+        save_if_changed(
+            &mut self.msf,
+            &mut self.categories,
+            &self.run.borrow(),
+            self.run_state.needs_save() || self.categories_changed || no_file,
+        )
+    }
+}
+
+// ask to save the split file if `changed`, picking where to save it if it is new
+fn save_if_changed(
+    msf: &mut MsfParser,
+    categories: &mut Categories,
+    run: &Run,
+    changed: bool,
+) -> Result<(), String> {
+    if changed && dialogs::save_check() {
+        if msf.no_path() {
+            if let Some(s) = dialogs::get_save_as() {
+                msf.set_filename(s);
+                write_splits(msf, categories, run)?;
+            }
+        } else {
+            write_splits(msf, categories, run)?;
+        }
+    }
+    Ok(())
+}
+
+// write the run to a LiveSplit split file, asking where the first time
+#[cfg(feature = "lss")]
+fn save_lss(path: &mut Option<String>, run: &Run) -> Result<(), String> {
+    if path.is_none() {
+        *path = dialogs::get_lss_save_as();
+    }
+    match path {
+        // LiveSplit files only hold the one run, so the other categories are left out
+        Some(p) => LssParser::new(p.clone())
+            .write(run)
+            .map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
+// write every category to the split file, with the one being timed up to date
+fn write_splits(msf: &mut MsfParser, categories: &mut Categories, run: &Run) -> Result<(), String> {
+    categories.set_run(categories.selected(), run.clone());
+    msf.write_categories(categories).map_err(|e| e.to_string())
+}
+
+// snapshot everything needed to pick the run back up later, including where it came from
+fn create_dump(
+    run_state: &RunState,
+    ren_state: &RenderState,
+    msf: &MsfParser,
+    categories: &Categories,
+) -> StateDump {
+    let mut d = run_state.create_state_dump();
+    ren_state.fill_dump(&mut d);
+    d.set_source(msf.filename(), categories.selected());
+    d
+}
+
+// real time from the start of the first run of a marathon to the end of the last one, which is
+// 0 until it starts when `running` and None if there is no marathon
+fn marathon_time(running: bool, start: Option<Instant>, end: Option<Instant>) -> Option<u128> {
+    match start {
+        Some(s) => Some(
+            end.unwrap_or_else(Instant::now)
+                .duration_since(s)
+                .as_nanos(),
+        ),
+        None if running => Some(0),
+        None => None,
+    }
+}
+
+// swap in another run, still racing the ghost that was loaded
+fn replace_run(run: &RefCell<Run>, new: &Run) {
+    let ghost = run.borrow().ghost().cloned();
+    run.replace(new.clone());
+    run.borrow_mut().set_ghost(ghost);
+}
+
+// read the split file at `path`, offering to pick another one if it fails
+fn load_categories(msf: &mut MsfParser, mut path: Option<String>) -> Option<Categories> {
+    while let Some(p) = path {
+        msf.set_filename(&p);
+        match msf.parse_categories() {
+            Ok(c) => return Some(c),
+            Err(e) => {
+                path = if dialogs::try_again(&e) {
+                    dialogs::get_run_path()
+                } else {
+                    None
                 }
-            } else {
-                self.msf.write(&self.run.borrow())?;
             }
         }
-        Ok(())
     }
+    None
+}
+
+// read the run to race against from an msf file, or an lss file if that feature is enabled
+fn parse_ghost(path: &str) -> Result<Run, ParseError> {
+    #[cfg(feature = "lss")]
+    if path.ends_with(".lss") {
+        return LssParser::new(path.to_owned()).parse();
+    }
+    MsfParser::new(path).parse()
 }
@@ -14,6 +14,15 @@ pub struct Keybinds {
     pub load_config: Keycode,
     pub dump_state: Keycode,
     pub load_state: Keycode,
+    pub pause_game_time: Keycode,
+    pub switch_method: Keycode,
+    pub undo_reset: Keycode,
+    pub switch_category: Keycode,
+    pub load_ghost: Keycode,
+    pub practice: Keycode,
+    pub load_marathon: Keycode,
+    #[cfg(feature = "lss")]
+    pub save_lss: Keycode,
 }
 
 impl Keybinds {
@@ -39,6 +48,23 @@ impl Keybinds {
                 .ok_or("Dump state keybind could not be parsed")?,
             load_state: Keycode::from_name(&raw.load_state)
                 .ok_or("Load state keybind could not be parsed")?,
+            pause_game_time: Keycode::from_name(&raw.pause_game_time)
+                .ok_or("Pause game time keybind could not be parsed")?,
+            switch_method: Keycode::from_name(&raw.switch_method)
+                .ok_or("Switch timing method keybind could not be parsed")?,
+            undo_reset: Keycode::from_name(&raw.undo_reset)
+                .ok_or("Undo reset keybind could not be parsed")?,
+            switch_category: Keycode::from_name(&raw.switch_category)
+                .ok_or("Switch category keybind could not be parsed")?,
+            load_ghost: Keycode::from_name(&raw.load_ghost)
+                .ok_or("Load ghost keybind could not be parsed")?,
+            practice: Keycode::from_name(&raw.practice)
+                .ok_or("Practice keybind could not be parsed")?,
+            load_marathon: Keycode::from_name(&raw.load_marathon)
+                .ok_or("Load marathon keybind could not be parsed")?,
+            #[cfg(feature = "lss")]
+            save_lss: Keycode::from_name(&raw.save_lss)
+                .ok_or("Save LiveSplit file keybind could not be parsed")?,
         })
     }
 }
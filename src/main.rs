@@ -20,11 +20,17 @@ fn main() {
         error(&err);
     });
     let ttf = sdl2::ttf::init().unwrap();
-    let config = Config::open().unwrap();
+    let config = Config::open().unwrap_or_else(|err| {
+        error(&err.to_string());
+    });
     let tfont = config.tfont();
-    let tf_bytes = tfont.get_bytes().unwrap();
+    let tf_bytes = tfont.get_bytes().unwrap_or_else(|err| {
+        error(&err.to_string());
+    });
     let sfont = config.sfont();
-    let sf_bytes = sfont.get_bytes().unwrap();
+    let sf_bytes = sfont.get_bytes().unwrap_or_else(|err| {
+        error(&err.to_string());
+    });
     let rw = RWops::from_bytes(&tf_bytes.0).unwrap();
     let timer_font = ttf
         .load_font_at_index_from_rwops(rw, tf_bytes.1, tfont.size())
@@ -6,7 +6,7 @@ use mist_core::{
     timer::{
         format,
         state::{RunUpdate, SplitStatus, StateChange},
-        Comparison, Run,
+        stats, Comparison, Run, TimeType, TimingMethod,
     },
 };
 use sdl2::{
@@ -26,6 +26,8 @@ use std::{cell::RefCell, convert::TryInto, rc::Rc};
 const ALL_CHARS: &str =
     "AaBbCcDdEeFfGgHhIiJjKkLlMmNnOoPpQqRrSsTtUuVvWwXxYyZz`1234567890[]~!@#$%^&*(){}',./=\\-;\"<>?+|_:";
 const TIMER_CHARS: &str = "1234567890:.-";
+// how far splits inside a group are pushed to the right
+const SUBSPLIT_INDENT: i32 = 15;
 
 pub struct RenderState<'a, 'b> {
     run: Rc<RefCell<Run>>,
@@ -33,11 +35,18 @@ pub struct RenderState<'a, 'b> {
     creator: TextureCreator<WindowContext>,
     colors: Colors,
     splits: Vec<Split>,
+    groups: Vec<Split>,
+    rows: Vec<Row>,
     panels: Vec<RenderPanel>,
+    notes: Option<Texture>,
+    notes_lines: u32,
     map: FontMap,
     time_str: String,
     time_rounding: Option<u128>,
     is_running: bool,
+    practicing: bool,
+    // whether a split to practice is being picked
+    selecting: bool,
     rebuild: bool,
     timer_font: &'a Font<'b, 'a>,
     timer_height: u32,
@@ -51,7 +60,9 @@ pub struct RenderState<'a, 'b> {
     max_splits: usize,
     inline: bool,
     status: SplitStatus,
+    time_up: bool,
     comparison: Comparison,
+    method: TimingMethod,
     #[cfg(feature = "bg")]
     background: Background,
 }
@@ -62,6 +73,15 @@ enum Background {
     HasBackground { tex: Texture, rect: Rect },
 }
 
+// a line in the splits list
+#[derive(Clone, Copy, PartialEq)]
+enum Row {
+    Split(usize),
+    // a split shown under its expanded group
+    SubSplit(usize),
+    Group(usize),
+}
+
 struct FontMap {
     tex: Texture,
     coords: Vec<u32>,
@@ -95,15 +115,22 @@ impl<'a, 'b> RenderState<'a, 'b> {
                         let text = if golds { "Split (best)" } else { "Split (pb)" };
                         (text, p)
                     }
+                    p @ Panel::PossibleTimeSave => ("Possible Time Save", p),
+                    p @ Panel::TotalTimeSave => ("Total Time Save", p),
+                    p @ Panel::BestPossibleTime => ("Best Possible Time", p),
+                    p @ Panel::PreviousSegment => ("Previous Segment", p),
+                    p @ Panel::SegmentStats => ("Median (±σ)", p),
+                    p @ Panel::ResetRate => ("Reset Rate", p),
+                    p @ Panel::MarathonTotal => ("Marathon Total", p),
                 };
-                let time = if let Panel::SumOfBest = panel {
+                let time = if let Panel::SumOfBest | Panel::BestPossibleTime = panel {
                     let sob = run
                         .borrow()
                         .gold_times()
                         .iter()
                         .map(|t| t.val())
                         .sum::<u128>();
-                    format::split_time_text(sob)
+                    format::ns_split_time_text(sob)
                 } else {
                     "-  ".into()
                 };
@@ -122,7 +149,7 @@ impl<'a, 'b> RenderState<'a, 'b> {
                 if t == 0 || !times[idx].is_time() {
                     "-  ".into()
                 } else {
-                    format::split_time_text(t)
+                    format::ns_split_time_text(t)
                 }
             })
             .collect();
@@ -146,6 +173,14 @@ impl<'a, 'b> RenderState<'a, 'b> {
                 )
             })
             .collect();
+        let groups = render_groups(
+            &run.borrow(),
+            &run.borrow().pb_times().clone(),
+            splits_font,
+            &creator,
+            config.colors().text,
+        )?;
+        let rows = build_rows(&run.borrow(), 0);
         let splits_height = splits_font.size_of(ALL_CHARS).map_err(|_| get_error())?.1;
         let timer_height = timer_font.size_of(TIMER_CHARS).map_err(|_| get_error())?.1;
         let bottom_index: usize;
@@ -153,29 +188,22 @@ impl<'a, 'b> RenderState<'a, 'b> {
         let max_initial_splits = ((canvas.viewport().height() - timer_height)
             / ((splits_height * (1 + !config.inline_splits() as u32)) + 5))
             as usize;
-        if splits.is_empty() {
+        if rows.is_empty() {
             max_splits = 0;
             bottom_index = 0;
-        } else if max_initial_splits > splits.len() {
-            bottom_index = splits.len() - 1;
-            max_splits = splits.len();
+        } else if max_initial_splits > rows.len() {
+            bottom_index = rows.len() - 1;
+            max_splits = rows.len();
         } else {
             max_splits = max_initial_splits;
             bottom_index = max_initial_splits - 1;
         }
-        let time_str = if run.borrow().offset().is_time() {
-            format!(
-                "-{}",
-                format::ms_to_readable(run.borrow().offset().val(), None)
-            )
-        } else {
-            "0.000".into()
-        };
+        let time_str = idle_time_text(&run.borrow());
         canvas
             .window_mut()
             .set_minimum_size(
                 100,
-                timer_height + 20 + (splits_height * panels.len() as u32),
+                timer_height + 20 + (splits_height * (panels.len() as u32 + config.notes_lines())),
             )
             .map_err(|_| get_error())?;
         canvas
@@ -187,15 +215,21 @@ impl<'a, 'b> RenderState<'a, 'b> {
             ))
             .map_err(|_| get_error())?;
         canvas.present();
-        Ok(Self {
+        let mut state = Self {
             run,
             colors: config.colors(),
             splits,
+            groups,
+            rows,
             panels,
+            notes: None,
+            notes_lines: config.notes_lines(),
             map: FontMap::generate(timer_font, &creator, config.colors().text)?,
             time_str,
             time_rounding: config.rounding(),
             is_running: false,
+            practicing: false,
+            selecting: false,
             rebuild: false,
             timer_font,
             timer_height,
@@ -209,34 +243,55 @@ impl<'a, 'b> RenderState<'a, 'b> {
             max_splits,
             inline: config.inline_splits(),
             status: SplitStatus::None,
+            time_up: false,
             comparison: Comparison::PersonalBest,
+            method: TimingMethod::RealTime,
             #[cfg(feature = "bg")]
             background: Background::load(config, canvas.viewport(), &creator)?,
             canvas,
             creator,
-        })
+        };
+        state.rebuild_notes()?;
+        state.rebuild_split_panels()?;
+        Ok(state)
     }
 
     pub fn update(&mut self, update: RunUpdate) -> Result<(), String> {
-        if update.status != self.status {
+        let (time, split_time) = match self.method {
+            TimingMethod::RealTime => (update.time, update.split_time),
+            TimingMethod::GameTime => (update.game_time, update.game_split_time),
+        };
+        // the main timer shows the time left instead when counting down
+        let shown = update.remaining.unwrap_or(time);
+        if update.status != self.status || update.time_up != self.time_up {
             self.status = update.status;
-            let color = self.convert_color(self.status);
+            self.time_up = update.time_up;
+            // only the main timer shows that the countdown ran out, split deltas keep their colors
+            let color = if self.time_up {
+                self.colors.time_up
+            } else {
+                self.convert_color(self.status)
+            };
             self.map = FontMap::generate(self.timer_font, &self.creator, color).unwrap();
         }
         if self.status != SplitStatus::None {
             for panel in &mut self.panels {
                 match *panel.panel_type() {
                     Panel::Pace { golds }
-                        if self.run.borrow().pb_times()[self.current].raw() != 0 =>
+                        if self.run.borrow().pb_times_of(self.method)[self.current].raw() != 0 =>
                     {
                         let r = self.run.borrow();
-                        let times = if golds { r.gold_times() } else { r.pb_times() };
-                        let pace = format::split_time_text(
+                        let times = if golds {
+                            r.gold_times_of(self.method)
+                        } else {
+                            r.pb_times_of(self.method)
+                        };
+                        let pace = format::ns_split_time_text(
                             times[self.current + 1..]
                                 .iter()
                                 .map(|t| t.val())
                                 .sum::<u128>()
-                                + update.time,
+                                + time,
                         );
                         panel.set_time(render_text(
                             pace,
@@ -247,23 +302,24 @@ impl<'a, 'b> RenderState<'a, 'b> {
                     }
                     Panel::CurrentSplitDiff { golds }
                         if self.splits.len() > 1
-                            && self.run.borrow().pb_times()[self.current].is_time() =>
+                            && self.run.borrow().pb_times_of(self.method)[self.current]
+                                .is_time() =>
                     {
                         let compare_time: u128 = if golds {
-                            self.run.borrow().gold_times()[self.current].raw()
+                            self.run.borrow().gold_times_of(self.method)[self.current].raw()
                         } else {
-                            self.run.borrow().pb_times()[self.current].raw()
+                            self.run.borrow().pb_times_of(self.method)[self.current].raw()
                         };
                         let time = if !golds {
-                            if update.split_time < compare_time {
-                                format::diff_text(-((compare_time - update.split_time) as i128))
+                            if split_time < compare_time {
+                                format::ns_diff_text(-((compare_time - split_time) as i128))
                             } else {
-                                format::diff_text((update.split_time - compare_time) as i128)
+                                format::ns_diff_text((split_time - compare_time) as i128)
                             }
-                        } else if update.split_time < compare_time {
-                            format::diff_text(-((compare_time - update.split_time) as i128))
+                        } else if split_time < compare_time {
+                            format::ns_diff_text(-((compare_time - split_time) as i128))
                         } else {
-                            format::diff_text((update.split_time - compare_time) as i128)
+                            format::ns_diff_text((split_time - compare_time) as i128)
                         };
                         panel.set_time(render_text(
                             time,
@@ -272,6 +328,25 @@ impl<'a, 'b> RenderState<'a, 'b> {
                             self.colors.text,
                         )?);
                     }
+                    Panel::BestPossibleTime
+                        if self.current < self.run.borrow().gold_times_of(self.method).len() =>
+                    {
+                        let r = self.run.borrow();
+                        let golds = r.gold_times_of(self.method);
+                        // the current split can't take less than the time already spent on it
+                        let best = time.saturating_sub(split_time)
+                            + golds[self.current].val().max(split_time)
+                            + golds[self.current + 1..]
+                                .iter()
+                                .map(|t| t.val())
+                                .sum::<u128>();
+                        panel.set_time(render_text(
+                            format::ns_split_time_text(best),
+                            self.splits_font,
+                            &self.creator,
+                            self.colors.text,
+                        )?);
+                    }
                     _ => {}
                 }
             }
@@ -285,11 +360,11 @@ impl<'a, 'b> RenderState<'a, 'b> {
                 StateChange::Pause => {
                     self.is_running = false;
                     self.highlighted = usize::MAX;
-                    self.time_str = format::ms_to_readable(update.time, self.time_rounding);
+                    self.time_str = format::ns_to_readable(shown, self.time_rounding);
                 }
                 StateChange::Finish { .. } => {
                     self.is_running = false;
-                    self.time_str = format::ms_to_readable(update.time, self.time_rounding);
+                    self.time_str = format::ns_to_readable(shown, self.time_rounding);
                     self.highlighted = usize::MAX;
                     self.rebuild = true;
                 }
@@ -301,30 +376,34 @@ impl<'a, 'b> RenderState<'a, 'b> {
                 } => {
                     if !self.run.borrow().splits().is_empty() {
                         if status == SplitStatus::Gold {
-                            for panel in &mut self.panels {
-                                if *panel.panel_type() == Panel::SumOfBest {
-                                    panel.set_time(render_text(
-                                        format::split_time_text(
-                                            self.run
-                                                .borrow()
-                                                .gold_times()
-                                                .iter()
-                                                .map(|t| t.val())
-                                                .sum::<u128>(),
-                                        ),
-                                        self.splits_font,
-                                        &self.creator,
-                                        self.colors.text,
-                                    )?);
-                                }
-                            }
+                            self.rebuild_sum_of_best()?;
                         }
                         let color = self.convert_color(status);
-                        let time_str = if !self.run.borrow().pb_times()[self.current].is_time() {
+                        let time_str = if !self.comparison.segments(&self.run.borrow(), self.method)
+                            [self.current]
+                            .is_time()
+                        {
                             "-  ".into()
                         } else {
-                            format::diff_text(diff)
+                            format::ns_diff_text(diff)
                         };
+                        let segment =
+                            self.comparison.segments(&self.run.borrow(), self.method)[self.current];
+                        let prev_str = if time == 0 || !segment.is_time() {
+                            "-  ".into()
+                        } else {
+                            format::ns_diff_text(time as i128 - segment.val() as i128)
+                        };
+                        for panel in &mut self.panels {
+                            if *panel.panel_type() == Panel::PreviousSegment {
+                                panel.set_time(render_text(
+                                    &prev_str,
+                                    self.splits_font,
+                                    &self.creator,
+                                    self.colors.text,
+                                )?);
+                            }
+                        }
                         if time == 0 {
                             self.splits[self.current].set_cur(Some(render_text(
                                 "-  ",
@@ -339,7 +418,7 @@ impl<'a, 'b> RenderState<'a, 'b> {
                                 &self.creator,
                                 color,
                             )?));
-                            let time_str = format::split_time_text(update.time);
+                            let time_str = format::ns_split_time_text(time);
                             self.splits[self.current].set_cur(Some(render_text(
                                 &time_str,
                                 self.splits_font,
@@ -349,45 +428,88 @@ impl<'a, 'b> RenderState<'a, 'b> {
                         }
                     }
                 }
+                StateChange::GhostDiff { idx, diff } => {
+                    let color = if diff < 0 {
+                        self.colors.ahead
+                    } else {
+                        self.colors.behind
+                    };
+                    self.splits[idx].set_ghost(Some(render_text(
+                        format::ns_diff_text(diff),
+                        self.splits_font,
+                        &self.creator,
+                        color,
+                    )?));
+                }
+                StateChange::ExitGroup {
+                    idx,
+                    status,
+                    time,
+                    diff,
+                } => {
+                    let color = self.convert_color(status);
+                    self.groups[idx].set_cur(Some(render_text(
+                        format::ns_split_time_text(time),
+                        self.splits_font,
+                        &self.creator,
+                        self.colors.text,
+                    )?));
+                    self.groups[idx].set_diff(Some(render_text(
+                        format::ns_diff_text(diff),
+                        self.splits_font,
+                        &self.creator,
+                        color,
+                    )?));
+                }
+                StateChange::EnterPractice { .. } => {
+                    self.practicing = true;
+                    self.selecting = false;
+                    self.set_title()?;
+                }
                 StateChange::EnterSplit { idx } => {
                     self.is_running = true;
                     // if we just unsplitted, remove the old textures
                     if idx < self.current {
                         self.splits[idx].set_cur(None);
                         self.splits[idx].set_diff(None);
+                        self.splits[idx].set_ghost(None);
+                        let group = self.run.borrow().group_of(idx);
+                        if let Some(g) = group {
+                            self.groups[g].set_cur(None);
+                            self.groups[g].set_diff(None);
+                        }
                     }
                     self.current = idx;
-                    if self.current > self.bottom_index {
-                        self.top_index += self.current - self.bottom_index;
-                        self.bottom_index = self.current;
-                    } else if self.current < self.top_index {
-                        self.bottom_index -= self.top_index - self.current;
-                        self.top_index = self.current;
-                    }
+                    self.rebuild_rows();
+                    self.scroll_to_current();
                     self.update_highlighted();
+                    self.rebuild_notes()?;
+                    self.rebuild_split_panels()?;
                 }
                 StateChange::Reset { .. } => {
                     self.current = 0;
                     self.highlighted = usize::MAX;
+                    self.top_index = 0;
                     if self.max_splits == 0 {
                         self.bottom_index = 0;
                     } else {
                         self.bottom_index = self.max_splits - 1;
                     }
-                    if self.run.borrow().offset().is_time() {
-                        self.time_str = format!(
-                            "-{}",
-                            format::ms_to_readable(self.run.borrow().offset().val(), None)
-                        );
-                    } else {
-                        self.time_str = "0.000".into();
-                    }
-                    for split in &mut self.splits {
+                    self.time_str = idle_time_text(&self.run.borrow());
+                    for split in self.splits.iter_mut().chain(self.groups.iter_mut()) {
                         split.set_cur(None);
                         split.set_diff(None);
+                        split.set_ghost(None);
                     }
+                    self.rebuild_rows();
+                    self.rebuild_notes()?;
                     for panel in &mut self.panels {
-                        if !matches!(panel.panel_type(), Panel::SumOfBest) {
+                        if matches!(
+                            panel.panel_type(),
+                            Panel::Pace { .. }
+                                | Panel::CurrentSplitDiff { .. }
+                                | Panel::PreviousSegment
+                        ) {
                             panel.set_time(render_text(
                                 "-  ",
                                 self.splits_font,
@@ -396,15 +518,56 @@ impl<'a, 'b> RenderState<'a, 'b> {
                             )?);
                         }
                     }
+                    self.rebuild_sum_of_best()?;
+                    self.rebuild_split_panels()?;
                     self.is_running = false;
+                    if self.practicing {
+                        self.practicing = false;
+                        self.set_title()?;
+                    }
                 }
                 StateChange::ComparisonChanged { comp } => {
                     self.comparison = comp;
                     self.rebuild = true;
                 }
+                StateChange::TimingMethodChanged { method } => {
+                    self.method = method;
+                    self.rebuild = true;
+                    self.rebuild_sum_of_best()?;
+                }
                 StateChange::EnterOffset => {
                     self.is_running = true;
                 }
+                StateChange::UndoFinish => {
+                    self.splits[self.current].set_cur(None);
+                    self.splits[self.current].set_diff(None);
+                    self.splits[self.current].set_ghost(None);
+                    let group = self.run.borrow().group_of(self.current);
+                    if let Some(g) = group {
+                        self.groups[g].set_cur(None);
+                        self.groups[g].set_diff(None);
+                    }
+                    self.rebuild = true;
+                    self.rebuild_sum_of_best()?;
+                }
+                StateChange::ResetGoldsKept => {
+                    self.rebuild = true;
+                    self.rebuild_sum_of_best()?;
+                }
+                StateChange::ResetUndone { mut dump } => {
+                    let time = match dump.method {
+                        TimingMethod::RealTime => dump.time,
+                        TimingMethod::GameTime => dump.game_time,
+                    };
+                    dump.set_render_info(
+                        0,
+                        self.max_splits.saturating_sub(1),
+                        format::ns_to_readable(time, self.time_rounding),
+                    );
+                    self.read_dump(&dump)?;
+                    self.is_running = false;
+                    self.highlighted = usize::MAX;
+                }
                 _ => {}
             }
         }
@@ -412,10 +575,10 @@ impl<'a, 'b> RenderState<'a, 'b> {
             if update.offset {
                 self.time_str = format!(
                     "-{}",
-                    format::ms_to_readable(self.run.borrow().offset().val() - update.time, None)
+                    format::ns_to_readable(self.run.borrow().offset().val() - update.time, None)
                 );
             } else {
-                self.time_str = format::ms_to_readable(update.time, None);
+                self.time_str = format::ns_to_readable(shown, None);
             }
         }
         self.update_highlighted();
@@ -423,7 +586,7 @@ impl<'a, 'b> RenderState<'a, 'b> {
     }
 
     pub fn scroll(&mut self, y: i32) {
-        if y == -1 && !self.splits.is_empty() && self.bottom_index < self.splits.len() - 1 {
+        if y == -1 && !self.rows.is_empty() && self.bottom_index < self.rows.len() - 1 {
             self.bottom_index += 1;
             self.top_index += 1;
         } else if y == 1 && self.top_index != 0 {
@@ -436,28 +599,28 @@ impl<'a, 'b> RenderState<'a, 'b> {
     pub fn win_resize(&mut self, y: u32) {
         let row_height = self.splits_height + 5 + (!self.inline as u32 * self.splits_height);
         let all_rows_height = row_height * self.max_splits as u32;
-        let bottom_height = self.timer_height + (self.splits_height * self.panels.len() as u32);
+        let bottom_height = self.timer_height + self.footer_height();
         if y - bottom_height > all_rows_height + row_height {
             let diff = (((y - bottom_height) - all_rows_height) / row_height) as usize;
-            if self.max_splits + diff < self.splits.len() {
+            if self.max_splits + diff < self.rows.len() {
                 self.max_splits += diff;
             } else {
-                self.max_splits = self.splits.len();
+                self.max_splits = self.rows.len();
             }
             if self.top_index > diff {
                 self.top_index -= diff;
             } else if self.top_index != 0 {
                 let bottom_change = diff - self.top_index;
                 self.top_index = 0;
-                if self.bottom_index + bottom_change < self.splits.len() - 1 {
+                if self.bottom_index + bottom_change < self.rows.len() - 1 {
                     self.bottom_index += bottom_change;
                 } else {
-                    self.bottom_index = self.splits.len() - 1;
+                    self.bottom_index = self.rows.len() - 1;
                 }
-            } else if !self.splits.is_empty() && self.bottom_index + diff < self.splits.len() - 1 {
+            } else if !self.rows.is_empty() && self.bottom_index + diff < self.rows.len() - 1 {
                 self.bottom_index += diff;
-            } else if !self.splits.is_empty() {
-                self.bottom_index = self.splits.len() - 1;
+            } else if !self.rows.is_empty() {
+                self.bottom_index = self.rows.len() - 1;
             } else {
                 self.bottom_index = 0;
             }
@@ -488,6 +651,7 @@ impl<'a, 'b> RenderState<'a, 'b> {
         #[cfg(feature = "bg")]
         self.render_bg()?;
 
+        self.render_notes()?;
         self.render_panels()?;
         self.render_rows()?;
         self.render_time()?;
@@ -502,11 +666,18 @@ impl<'a, 'b> RenderState<'a, 'b> {
                 if t == 0 {
                     "-  ".into()
                 } else {
-                    format::split_time_text(t)
+                    format::ns_split_time_text(t)
                 }
             })
             .collect();
         self.splits = vec![];
+        self.groups = render_groups(
+            &self.run.borrow(),
+            &self.run.borrow().pb_times().clone(),
+            self.splits_font,
+            &self.creator,
+            self.colors.text,
+        )?;
         for (idx, name) in self.run.borrow().splits().iter().enumerate() {
             self.splits.push(Split::new(
                 render_text(name, self.splits_font, &self.creator, self.colors.text)?,
@@ -520,34 +691,83 @@ impl<'a, 'b> RenderState<'a, 'b> {
                 None,
             ));
         }
-        if self.run.borrow().offset().is_time() {
-            self.time_str = format!(
-                "-{}",
-                format::ms_to_readable(self.run.borrow().offset().val(), None)
-            );
-        } else {
-            self.time_str = "0.000".into();
-        }
+        self.time_str = idle_time_text(&self.run.borrow());
         self.top_index = 0;
         self.highlighted = usize::MAX;
         self.current = 0;
         self.status = SplitStatus::None;
+        // a new RunState starts over at these
+        self.comparison = Comparison::PersonalBest;
+        self.method = TimingMethod::RealTime;
+        self.rows = build_rows(&self.run.borrow(), 0);
         let max_initial_splits = ((self.canvas.viewport().height() - self.timer_height)
             / ((self.splits_height * (1 + !self.inline as u32)) + 5))
             as usize;
-        if self.splits.is_empty() {
+        if self.rows.is_empty() {
             self.max_splits = 0;
             self.bottom_index = 0;
-        } else if max_initial_splits > self.splits.len() {
-            self.max_splits = self.splits.len();
-            self.bottom_index = self.splits.len() - 1;
+        } else if max_initial_splits > self.rows.len() {
+            self.max_splits = self.rows.len();
+            self.bottom_index = self.rows.len() - 1;
         } else {
             self.max_splits = max_initial_splits;
             self.bottom_index = max_initial_splits - 1;
         }
+        self.rebuild_notes()?;
+        self.practicing = false;
+        self.set_title()
+    }
+
+    // highlight the split being picked to practice from, or stop with None
+    pub fn select_split(&mut self, split: Option<usize>) -> Result<(), String> {
+        self.selecting = split.is_some();
+        self.current = split.unwrap_or(0);
+        self.rebuild_rows();
+        self.scroll_to_current();
+        self.update_highlighted();
+        self.rebuild_notes()
+    }
+
+    fn set_title(&mut self) -> Result<(), String> {
+        let title = format!(
+            "mist: {} ({}){}",
+            self.run.borrow().game_title(),
+            self.run.borrow().category(),
+            if self.practicing { " - practice" } else { "" }
+        );
+        self.canvas
+            .window_mut()
+            .set_title(&title)
+            .map_err(|_| get_error())
+    }
+
+    /// Show the total time of a marathon in its panel. [`None`] when not running a marathon.
+    pub fn set_marathon(&mut self, time: Option<u128>) -> Result<(), String> {
+        let text = match time {
+            Some(t) => format::ns_split_time_text(t),
+            None => "-  ".into(),
+        };
+        for panel in &mut self.panels {
+            if *panel.panel_type() == Panel::MarathonTotal {
+                panel.set_time(render_text(
+                    &text,
+                    self.splits_font,
+                    &self.creator,
+                    self.colors.text,
+                )?);
+            }
+        }
         Ok(())
     }
 
+    // the ghost only changes the comparison times and the deltas, so the rest of the run can stay
+    pub fn reload_ghost(&mut self) -> Result<(), String> {
+        for split in &mut self.splits {
+            split.set_ghost(None);
+        }
+        self.rebuild_comparison()
+    }
+
     pub fn reload_config(
         self,
         config: &Config,
@@ -562,95 +782,177 @@ impl<'a, 'b> RenderState<'a, 'b> {
     }
 
     fn update_highlighted(&mut self) {
-        if self.is_running && self.current >= self.top_index && self.current <= self.bottom_index {
-            self.highlighted = self.current - self.top_index;
+        let current = self.current_row();
+        if (self.is_running || self.selecting)
+            && current >= self.top_index
+            && current <= self.bottom_index
+        {
+            self.highlighted = current - self.top_index;
         } else {
             self.highlighted = usize::MAX;
         }
     }
 
+    // index of the row showing the current split
+    fn current_row(&self) -> usize {
+        self.rows
+            .iter()
+            .position(|&r| r == Row::Split(self.current) || r == Row::SubSplit(self.current))
+            .unwrap_or(0)
+    }
+
+    fn scroll_to_current(&mut self) {
+        let current = self.current_row();
+        if current > self.bottom_index {
+            self.top_index += current - self.bottom_index;
+            self.bottom_index = current;
+        } else if current < self.top_index {
+            self.bottom_index -= self.top_index - current;
+            self.top_index = current;
+        }
+    }
+
+    // groups expand and collapse as the current split changes, so the number of rows can too
+    fn rebuild_rows(&mut self) {
+        if self.run.borrow().groups().is_empty() {
+            return;
+        }
+        self.rows = build_rows(&self.run.borrow(), self.current);
+        let row_height = self.splits_height + 5 + (!self.inline as u32 * self.splits_height);
+        let bottom_height = self.timer_height + self.footer_height();
+        let fit = (self
+            .canvas
+            .viewport()
+            .height()
+            .saturating_sub(bottom_height)
+            / row_height) as usize;
+        self.max_splits = fit.min(self.rows.len());
+        if self.max_splits == 0 {
+            self.top_index = 0;
+            self.bottom_index = 0;
+            return;
+        }
+        if self.top_index + self.max_splits > self.rows.len() {
+            self.top_index = self.rows.len() - self.max_splits;
+        }
+        self.bottom_index = self.top_index + self.max_splits - 1;
+    }
+
     fn rebuild_comparison(&mut self) -> Result<(), String> {
-        match self.comparison {
-            Comparison::None => {
-                for split in &mut self.splits {
-                    split.set_comp(render_text(
-                        "-  ",
-                        self.splits_font,
-                        &self.creator,
-                        self.colors.text,
-                    )?);
-                }
-            }
-            Comparison::Average => {
-                let mut i = 0;
-                let (attempts, times) = {
-                    let mut att = vec![];
-                    let mut tm = vec![];
-                    for sum in self.run.borrow().sum_times() {
-                        att.push(sum.0);
-                        tm.push(sum.1);
+        let segments = self.comparison.segments(&self.run.borrow(), self.method);
+        let split_times_raw: Vec<String> =
+            format::split_time_sum(&segments.iter().map(|t| t.raw()).collect::<Vec<_>>())
+                .iter()
+                .enumerate()
+                .map(|(idx, &t)| {
+                    if t == 0 || !segments[idx].is_time() {
+                        "-  ".into()
+                    } else {
+                        format::ns_split_time_text(t)
                     }
-                    (att, tm)
-                };
-                let mut avgs = vec![];
-                while i < attempts.len() {
-                    avgs.push(if attempts[i] != 0 {
-                        times[i] / attempts[i]
+                })
+                .collect();
+        for (split, time_str) in self.splits.iter_mut().zip(split_times_raw.iter()) {
+            split.set_comp(render_text(
+                time_str,
+                self.splits_font,
+                &self.creator,
+                self.colors.text,
+            )?);
+        }
+        for (idx, group) in self.run.borrow().groups().iter().enumerate() {
+            self.groups[idx].set_comp(render_text(
+                group_time_text(&segments[group.start()..=group.end()]),
+                self.splits_font,
+                &self.creator,
+                self.colors.text,
+            )?);
+        }
+        self.rebuild_split_panels()?;
+        Ok(())
+    }
+
+    // panels about the current split, which only change when the split, the comparison or the
+    // golds do
+    fn rebuild_split_panels(&mut self) -> Result<(), String> {
+        let stats = if self
+            .panels
+            .iter()
+            .any(|p| matches!(p.panel_type(), Panel::SegmentStats | Panel::ResetRate))
+        {
+            stats::segment_stats(&self.run.borrow(), self.method)
+        } else {
+            vec![]
+        };
+        let saves = {
+            let run = self.run.borrow();
+            self.comparison
+                .segments(&run, self.method)
+                .iter()
+                .zip(run.gold_times_of(self.method))
+                .map(|(comp, gold)| {
+                    if comp.is_time() && gold.is_time() {
+                        Some(comp.val().saturating_sub(gold.val()))
                     } else {
-                        attempts[i]
-                    });
-                    i += 1;
-                }
-                let split_times_raw: Vec<String> = format::split_time_sum(&avgs)
-                    .iter()
-                    .enumerate()
-                    .map(|(idx, &t)| {
-                        if t == 0 || !times[idx].is_time() {
-                            "-  ".into()
-                        } else {
-                            format::split_time_text(t)
-                        }
-                    })
-                    .collect();
-                i = 0;
-                while i < self.splits.len() {
-                    self.splits[i].set_comp(render_text(
-                        &split_times_raw[i],
-                        self.splits_font,
-                        &self.creator,
-                        self.colors.text,
-                    )?);
-                    i += 1;
-                }
-            }
-            c => {
-                let split_times = match c {
-                    Comparison::PersonalBest => self.run.borrow().pb_times_u128(),
-                    Comparison::Golds => self.run.borrow().gold_times_u128(),
-                    _ => unreachable!(),
-                };
-                let times = self.run.borrow().pb_times().clone();
-                let split_times_raw: Vec<String> = format::split_time_sum(&split_times)
-                    .iter()
-                    .enumerate()
-                    .map(|(idx, &t)| {
-                        if t == 0 || !times[idx].is_time() {
-                            "-  ".into()
-                        } else {
-                            format::split_time_text(t)
-                        }
-                    })
-                    .collect();
-                let mut i = 0;
-                while i < self.splits.len() {
-                    self.splits[i].set_comp(render_text(
-                        &split_times_raw[i],
-                        self.splits_font,
-                        &self.creator,
-                        self.colors.text,
-                    )?);
-                    i += 1;
+                        None
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+        let current = stats.get(self.current);
+        for panel in &mut self.panels {
+            let text = match panel.panel_type() {
+                Panel::PossibleTimeSave => saves
+                    .get(self.current)
+                    .copied()
+                    .flatten()
+                    .map(format::ns_split_time_text),
+                Panel::TotalTimeSave => saves
+                    .get(self.current..)
+                    .map(|s| format::ns_split_time_text(s.iter().flatten().sum())),
+                Panel::SegmentStats => {
+                    current
+                        .and_then(|s| Some((s.median?, s.std_dev?)))
+                        .map(|(median, dev)| {
+                            format!(
+                                "{} ±{}",
+                                format::ns_split_time_text(median as u128),
+                                format::ns_split_time_text(dev as u128)
+                            )
+                        })
                 }
+                Panel::ResetRate => current
+                    .filter(|s| s.reached != 0)
+                    .map(|s| format!("{:.0}%", s.reset_rate() * 100.0)),
+                _ => continue,
+            };
+            let text = text.unwrap_or_else(|| "-  ".into());
+            panel.set_time(render_text(
+                text,
+                self.splits_font,
+                &self.creator,
+                self.colors.text,
+            )?);
+        }
+        Ok(())
+    }
+
+    fn rebuild_sum_of_best(&mut self) -> Result<(), String> {
+        let sob = self
+            .run
+            .borrow()
+            .gold_times_of(self.method)
+            .iter()
+            .map(|t| t.val())
+            .sum::<u128>();
+        for panel in &mut self.panels {
+            if let Panel::SumOfBest | Panel::BestPossibleTime = panel.panel_type() {
+                panel.set_time(render_text(
+                    format::ns_split_time_text(sob),
+                    self.splits_font,
+                    &self.creator,
+                    self.colors.text,
+                )?);
             }
         }
         Ok(())
@@ -658,7 +960,7 @@ impl<'a, 'b> RenderState<'a, 'b> {
 
     fn render_rows(&mut self) -> Result<(), String> {
         let on_screen = if self.max_splits > 0 {
-            &self.splits[self.top_index..=self.bottom_index]
+            &self.rows[self.top_index..=self.bottom_index]
         } else {
             &[]
         };
@@ -667,7 +969,12 @@ impl<'a, 'b> RenderState<'a, 'b> {
         let mut row: Rect;
         let window_width = self.canvas.viewport().width();
         // draw each split name on the left of the screen
-        for (index, item) in on_screen.iter().enumerate() {
+        for (index, &line) in on_screen.iter().enumerate() {
+            let (item, indent) = match line {
+                Row::Split(idx) => (&self.splits[idx], 0),
+                Row::SubSplit(idx) => (&self.splits[idx], SUBSPLIT_INDENT),
+                Row::Group(idx) => (&self.groups[idx], 0),
+            };
             let TextureQuery { width, height, .. } = item.name().query();
             // draw the blue highlight box before drawing the text for the split with index current
             if index == self.highlighted {
@@ -675,7 +982,7 @@ impl<'a, 'b> RenderState<'a, 'b> {
                 self.canvas
                     .fill_rect(Rect::new(0, y - 1, window_width, incr_height as u32 + 5))?;
             }
-            row = Rect::new(0, y, width, height);
+            row = Rect::new(indent, y, width, height);
             self.canvas.copy(item.name(), None, Some(row))?;
             let num_y = if self.inline {
                 y
@@ -710,18 +1017,25 @@ impl<'a, 'b> RenderState<'a, 'b> {
                     tinfo
                 }
             };
+            let mut diff_end = window_width - tinfo_width - 25;
             if let Some(x) = item.diff() {
                 let TextureQuery {
                     width: dw,
                     height: dh,
                     ..
                 } = x.query();
-                row = Rect::new(
-                    ((window_width - tinfo_width - 25) - dw) as i32,
-                    num_y,
-                    dw,
-                    dh,
-                );
+                row = Rect::new((diff_end - dw) as i32, num_y, dw, dh);
+                self.canvas.copy(x, None, Some(row))?;
+                diff_end -= dw + 15;
+            }
+            // the delta to the ghost goes in its own column left of the diff
+            if let Some(x) = item.ghost() {
+                let TextureQuery {
+                    width: gw,
+                    height: gh,
+                    ..
+                } = x.query();
+                row = Rect::new(diff_end.saturating_sub(gw) as i32, num_y, gw, gh);
                 self.canvas.copy(x, None, Some(row))?;
             }
             self.canvas.set_draw_color(self.colors.line);
@@ -745,8 +1059,8 @@ impl<'a, 'b> RenderState<'a, 'b> {
             (self.timer_height - self.timer_font.find_glyph_metrics('0').unwrap().maxy as u32) / 2;
         let mut dst = Rect::new(
             0,
-            (h - (starting_y + (diff as f32 * (1.0 - self.ms_ratio)) as u32)
-                - (self.splits_height * self.panels.len() as u32)) as i32,
+            (h - (starting_y + (diff as f32 * (1.0 - self.ms_ratio)) as u32) - self.footer_height())
+                as i32,
             0,
             starting_y,
         );
@@ -762,10 +1076,7 @@ impl<'a, 'b> RenderState<'a, 'b> {
             dst.set_x(wdx);
             dst.set_width(dw);
             if idx == 3 {
-                dst.set_y(
-                    (h - self.timer_height - (self.splits_height * self.panels.len() as u32))
-                        as i32,
-                );
+                dst.set_y((h - self.timer_height - self.footer_height()) as i32);
                 dst.set_height(self.timer_height);
             }
             self.canvas.copy(&self.map.tex, Some(src), Some(dst))?;
@@ -773,7 +1084,58 @@ impl<'a, 'b> RenderState<'a, 'b> {
         Ok(())
     }
 
+    // height of everything drawn below the timer
+    fn footer_height(&self) -> u32 {
+        self.splits_height * (self.panels.len() as u32 + self.notes_lines)
+    }
+
+    // re-render the notes area for the current split
+    fn rebuild_notes(&mut self) -> Result<(), String> {
+        if self.notes_lines == 0 {
+            return Ok(());
+        }
+        let tex = match self.run.borrow().split_notes(self.current) {
+            Some(notes) => Some(render_wrapped_text(
+                notes,
+                self.splits_font,
+                &self.creator,
+                self.colors.text,
+                self.canvas.viewport().width().max(1),
+            )?),
+            None => None,
+        };
+        if let Some(n) = self.notes.as_ref() {
+            unsafe {
+                sdl2::sys::SDL_DestroyTexture(n.raw());
+            }
+        }
+        self.notes = tex;
+        Ok(())
+    }
+
+    fn render_notes(&mut self) -> Result<(), String> {
+        if let Some(ref tex) = self.notes {
+            let vp = self.canvas.viewport();
+            let TextureQuery { width, height, .. } = tex.query();
+            let area = self.splits_height * self.notes_lines;
+            // cut off whatever doesn't fit in the notes area
+            let rect = Rect::new(0, 0, width.min(vp.width()), height.min(area));
+            self.canvas.copy(
+                tex,
+                Some(rect),
+                Some(Rect::new(
+                    0,
+                    (vp.height() - area) as i32,
+                    rect.width(),
+                    rect.height(),
+                )),
+            )?;
+        }
+        Ok(())
+    }
+
     fn render_panels(&mut self) -> Result<(), String> {
+        let notes_height = self.splits_height * self.notes_lines;
         let mut num = 1;
         for panel in &self.panels {
             let TextureQuery { width, height, .. } = panel.text().query();
@@ -782,7 +1144,7 @@ impl<'a, 'b> RenderState<'a, 'b> {
                 None,
                 Some(Rect::new(
                     0,
-                    (self.canvas.viewport().height() - (num * height)) as i32,
+                    (self.canvas.viewport().height() - notes_height - (num * height)) as i32,
                     width,
                     height,
                 )),
@@ -793,7 +1155,7 @@ impl<'a, 'b> RenderState<'a, 'b> {
                 None,
                 Some(Rect::new(
                     (self.canvas.viewport().width() - width) as i32,
-                    (self.canvas.viewport().height() - (num * height)) as i32,
+                    (self.canvas.viewport().height() - notes_height - (num * height)) as i32,
                     width,
                     height,
                 )),
@@ -821,26 +1183,40 @@ impl<'a, 'b> RenderState<'a, 'b> {
         self.time_str = dump.time_str.clone();
         self.status = dump.status;
         self.comparison = dump.comparison;
+        self.method = dump.method;
         self.current = dump.current_split;
+        self.rebuild_rows();
+        self.scroll_to_current();
+        self.rebuild_notes()?;
         self.rebuild_comparison()?;
+        self.rebuild_sum_of_best()?;
         self.rebuild_current(dump)?;
         Ok(())
     }
 
     fn rebuild_current(&mut self, dump: &StateDump) -> Result<(), String> {
+        let (dump_times, dump_golds) = match dump.method {
+            TimingMethod::GameTime if dump.game_run_times.len() == dump.run_times.len() => {
+                (&dump.game_run_times, &dump.game_run_golds)
+            }
+            _ => (&dump.run_times, &dump.run_golds),
+        };
         let raw_diffs = dump.run_diffs.iter().map(|v| v.raw()).collect::<Vec<_>>();
         let diff_sums = format::split_time_sum(&raw_diffs);
-        let stats = calculate_statuses(&raw_diffs, &dump.run_golds);
+        let stats = calculate_statuses(&raw_diffs, dump_golds);
+        // ghost deltas aren't kept in dumps
+        for split in &mut self.splits {
+            split.set_ghost(None);
+        }
         let run_times = format::split_time_sum(
-            &dump
-                .run_times
+            &dump_times
                 .iter()
                 .map_while(|t| t.to_option())
                 .collect::<Vec<_>>(),
         );
         for (i, &time) in run_times.iter().enumerate() {
-            let time_str = if dump.run_times[i].is_time() {
-                format::split_time_text(time)
+            let time_str = if dump_times[i].is_time() {
+                format::ns_split_time_text(time)
             } else {
                 "-  ".into()
             };
@@ -850,7 +1226,7 @@ impl<'a, 'b> RenderState<'a, 'b> {
                 &self.creator,
                 self.colors.text,
             )?));
-            let time_str = format::diff_text(diff_sums[i]);
+            let time_str = format::ns_diff_text(diff_sums[i]);
             let color = self.convert_color(stats[i]);
             self.splits[i].set_diff(Some(render_text(
                 &time_str,
@@ -859,6 +1235,40 @@ impl<'a, 'b> RenderState<'a, 'b> {
                 color,
             )?));
         }
+        let segments = dump.comparison.segments(&dump.run, dump.method);
+        for (idx, group) in dump.run.groups().iter().enumerate() {
+            let times = &dump_times[group.start()..=group.end()];
+            if times.iter().any(|t| t.is_none()) {
+                continue;
+            }
+            let time: u128 = times.iter().map(|t| t.raw()).sum();
+            let comp: u128 = segments[group.start()..=group.end()]
+                .iter()
+                .map(|t| t.raw())
+                .sum();
+            let gold = group.gold_time_of(dump.method);
+            let diff = time as i128 - comp as i128;
+            let status = if gold.is_none() || time < gold.val() {
+                SplitStatus::Gold
+            } else if diff < 0 {
+                SplitStatus::Ahead
+            } else {
+                SplitStatus::Behind
+            };
+            let color = self.convert_color(status);
+            self.groups[idx].set_cur(Some(render_text(
+                format::ns_split_time_text(time),
+                self.splits_font,
+                &self.creator,
+                self.colors.text,
+            )?));
+            self.groups[idx].set_diff(Some(render_text(
+                format::ns_diff_text(diff),
+                self.splits_font,
+                &self.creator,
+                color,
+            )?));
+        }
         Ok(())
     }
 
@@ -875,6 +1285,17 @@ impl<'a, 'b> RenderState<'a, 'b> {
     }
 }
 
+// what the main timer shows before an attempt starts
+fn idle_time_text(run: &Run) -> String {
+    if run.offset().is_time() {
+        format!("-{}", format::ns_to_readable(run.offset().val(), None))
+    } else if let Some(limit) = run.countdown() {
+        format::ns_to_readable(limit, None)
+    } else {
+        "0.000".into()
+    }
+}
+
 fn calculate_statuses(diffs: &[i128], golds: &[bool]) -> Vec<SplitStatus> {
     let mut sum = 0;
     let mut ret = vec![];
@@ -1041,6 +1462,23 @@ impl Background {
     }
 }
 
+// like `render_text` but breaks lines at newlines and at `width`
+fn render_wrapped_text<C: Into<Color>>(
+    text: &str,
+    font: &sdl2::ttf::Font,
+    creator: &sdl2::render::TextureCreator<sdl2::video::WindowContext>,
+    color: C,
+    width: u32,
+) -> Result<Texture, String> {
+    let sur = font
+        .render(text)
+        .blended_wrapped(color, width)
+        .map_err(|_| get_error())?;
+    creator
+        .create_texture_from_surface(sur)
+        .map_err(|_| get_error())
+}
+
 fn render_text<T: ToString, C: Into<Color>>(
     text: T,
     font: &sdl2::ttf::Font,
@@ -1055,3 +1493,60 @@ fn render_text<T: ToString, C: Into<Color>>(
         .create_texture_from_surface(sur)
         .map_err(|_| get_error())
 }
+
+// every row of the splits list, with only the group containing `current` expanded
+fn build_rows(run: &Run, current: usize) -> Vec<Row> {
+    let mut rows = vec![];
+    let mut idx = 0;
+    while idx < run.splits().len() {
+        match run.group_of(idx) {
+            Some(g) => {
+                let group = &run.groups()[g];
+                rows.push(Row::Group(g));
+                if group.contains(current) {
+                    rows.extend((group.start()..=group.end()).map(Row::SubSplit));
+                }
+                idx = group.end() + 1;
+            }
+            None => {
+                rows.push(Row::Split(idx));
+                idx += 1;
+            }
+        }
+    }
+    rows
+}
+
+// header rows for each group, comparing against `segments`
+fn render_groups(
+    run: &Run,
+    segments: &[TimeType],
+    font: &Font,
+    creator: &TextureCreator<WindowContext>,
+    color: (u8, u8, u8, u8),
+) -> Result<Vec<Split>, String> {
+    run.groups()
+        .iter()
+        .map(|group| {
+            Ok(Split::new(
+                render_text(group.name(), font, creator, color)?,
+                render_text(
+                    group_time_text(&segments[group.start()..=group.end()]),
+                    font,
+                    creator,
+                    color,
+                )?,
+                None,
+                None,
+            ))
+        })
+        .collect()
+}
+
+fn group_time_text(segments: &[TimeType]) -> String {
+    if segments.iter().all(|t| t.is_time()) {
+        format::ns_split_time_text(segments.iter().map(|t| t.val()).sum())
+    } else {
+        "-  ".into()
+    }
+}
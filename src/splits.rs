@@ -5,6 +5,7 @@ pub struct Split {
     name: Texture,
     comp: Texture,
     current: Option<Texture>,
+    ghost: Option<Texture>,
 }
 
 impl Split {
@@ -19,6 +20,7 @@ impl Split {
             name,
             comp,
             current,
+            ghost: None,
         }
     }
     pub fn name(&self) -> &Texture {
@@ -33,6 +35,9 @@ impl Split {
     pub fn diff(&self) -> &Option<Texture> {
         &self.diff
     }
+    pub fn ghost(&self) -> &Option<Texture> {
+        &self.ghost
+    }
     // Have to destroy the textures or else we will eat all the memory.
     // No setting textures after the canvas is dead, I guess? Not that there's any reason to do that anyway...
     pub fn set_comp(&mut self, tex: Texture) {
@@ -57,4 +62,12 @@ impl Split {
         };
         self.diff = texture;
     }
+    pub fn set_ghost(&mut self, texture: Option<Texture>) {
+        if let Some(g) = self.ghost.as_ref() {
+            unsafe {
+                sdl2::sys::SDL_DestroyTexture(g.raw());
+            }
+        };
+        self.ghost = texture;
+    }
 }